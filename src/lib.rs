@@ -49,39 +49,42 @@
 //! <details>
 //!     <summary>XML Elements</summary>
 //!
-//! - [ ] `activelock`
-//! - [X] `allprop`: internally implemented for
-//!   [`Propfind`](crate::xml::elements::Propfind)
+//! - [X] [`activelock`](crate::xml::elements::ActiveLock)
+//! - [X] [`allprop`](crate::xml::elements::Allprop)
 //! - [X] `collection`: internally implemented for
 //!   [`ResourceType`](crate::xml::properties::ResourceType)
-//! - [ ] `depth`
-//! - [ ] `error`: currently just a string
-//! - [ ] `exclusive`
+//! - [X] [`depth`](crate::xml::elements::Depth)
+//! - [X] [`error`](crate::xml::elements::Error)
+//! - [X] `exclusive`: internally implemented for
+//!   [`LockScope`](crate::xml::elements::LockScope)
 //! - [X] [`href`](crate::xml::elements::Href)
 //! - [X] [`include`](crate::xml::elements::Include)
-//! - [ ] `location`
-//! - [ ] `lockentry`
-//! - [ ] `lockinfo`
-//! - [ ] `lockroot`
-//! - [ ] `lockscope`
-//! - [ ] `locktoken`
-//! - [ ] `locktype`
+//! - [X] [`location`](crate::xml::elements::Location)
+//! - [X] [`lockentry`](crate::xml::elements::LockEntry)
+//! - [X] [`lockinfo`](crate::xml::elements::LockInfo)
+//! - [X] [`lockroot`](crate::xml::elements::LockRoot)
+//! - [X] [`lockscope`](crate::xml::elements::LockScope)
+//! - [X] [`locktoken`](crate::xml::elements::LockToken)
+//! - [X] [`locktype`](crate::xml::elements::LockType)
 //! - [X] [`multistatus`](crate::xml::elements::Multistatus)
-//! - [ ] `owner`
+//! - [X] [`owner`](crate::xml::elements::Owner)
 //! - [X] [`prop`](crate::xml::elements::Properties)
-//! - [ ] `propertyupdate`
+//! - [X] [`propertyupdate`](crate::xml::elements::PropertyUpdate)
 //! - [X] [`propfind`](crate::xml::elements::Propfind)
-//! - [X] `propname`: internally implemented for
-//!   [`Propfind`](crate::xml::elements::Propfind)
+//! - [X] [`propname`](crate::xml::elements::Propname)
 //! - [X] [`propstat`](crate::xml::elements::Propstat)
-//! - [ ] `remove`
+//! - [X] `remove`: implemented as
+//!   [`Instruction::Remove`](crate::xml::elements::Instruction::Remove)
 //! - [X] [`response`](crate::xml::elements::Response)
 //! - [X] [`responsedescription`](crate::xml::elements::ResponseDescription)
-//! - [ ] `set`
-//! - [ ] `shared`
+//! - [X] `set`: implemented as
+//!   [`Instruction::Set`](crate::xml::elements::Instruction::Set)
+//! - [X] `shared`: internally implemented for
+//!   [`LockScope`](crate::xml::elements::LockScope)
 //! - [ ] `status`
-//! - [ ] `timeout`
-//! - [ ] `write`
+//! - [X] [`timeout`](crate::xml::elements::Timeout)
+//! - [X] `write`: internally implemented for
+//!   [`LockType`](crate::xml::elements::LockType)
 //!
 //! </details>
 //! <details>
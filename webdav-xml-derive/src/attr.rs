@@ -0,0 +1,141 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{spanned::Spanned, Attribute, Ident, LitStr};
+
+/// The options of a `#[dav(...)]` attribute.
+///
+/// All options are parsed everywhere, the expansion rejects the ones that
+/// don't make sense in a specific position.
+#[derive(Default)]
+pub(crate) struct Attrs {
+    pub namespace: Option<LitStr>,
+    pub prefix: Option<LitStr>,
+    pub rename: Option<LitStr>,
+    pub krate: Option<LitStr>,
+    pub text: Option<Span>,
+}
+
+impl Attrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut this = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("dav")) {
+            attr.parse_nested_meta(|meta| {
+                let option = if meta.path.is_ident("namespace") {
+                    &mut this.namespace
+                } else if meta.path.is_ident("prefix") {
+                    &mut this.prefix
+                } else if meta.path.is_ident("rename") {
+                    &mut this.rename
+                } else if meta.path.is_ident("crate") {
+                    &mut this.krate
+                } else if meta.path.is_ident("text") {
+                    if this.text.is_some() {
+                        return Err(meta.error("duplicate `text` option"));
+                    }
+                    this.text = Some(meta.path.span());
+                    return Ok(());
+                } else {
+                    return Err(
+                        meta.error("expected `namespace`, `prefix`, `rename`, `crate` or `text`")
+                    );
+                };
+
+                if option.is_some() {
+                    let name = meta.path.to_token_stream();
+                    return Err(meta.error(format!("duplicate `{name}` option")));
+                }
+                *option = Some(meta.value()?.parse()?);
+                Ok(())
+            })?;
+        }
+
+        Ok(this)
+    }
+
+    /// Fails if any of the given options is set.
+    pub fn reject(&self, options: &[&str], reason: &str) -> syn::Result<()> {
+        let spans = [
+            ("namespace", self.namespace.as_ref().map(LitStr::span)),
+            ("prefix", self.prefix.as_ref().map(LitStr::span)),
+            ("rename", self.rename.as_ref().map(LitStr::span)),
+            ("crate", self.krate.as_ref().map(LitStr::span)),
+            ("text", self.text),
+        ];
+
+        for (option, span) in spans {
+            if let (true, Some(span)) = (options.contains(&option), span) {
+                return Err(syn::Error::new(
+                    span,
+                    format!("`{option}` is not supported {reason}"),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The name of an element, as tokens for the constants of its `Element`
+/// implementation.
+#[derive(Clone)]
+pub(crate) struct Name {
+    pub namespace: TokenStream,
+    pub prefix: TokenStream,
+    pub local_name: LitStr,
+}
+
+impl Name {
+    /// The name of a derived element, in the `DAV:` namespace by default.
+    pub fn container(attrs: &Attrs, ident: &Ident, krate: &TokenStream) -> syn::Result<Self> {
+        let default = || (quote!(#krate::DAV_NAMESPACE), quote!(#krate::DAV_PREFIX));
+        Self::new(attrs, ident, default)
+    }
+    /// The name of a child element, in the parent's namespace by default.
+    pub fn child(attrs: &Attrs, ident: &Ident, parent: &Name) -> syn::Result<Self> {
+        let default = || (parent.namespace.clone(), parent.prefix.clone());
+        Self::new(attrs, ident, default)
+    }
+    fn new(
+        attrs: &Attrs,
+        ident: &Ident,
+        default: impl FnOnce() -> (TokenStream, TokenStream),
+    ) -> syn::Result<Self> {
+        let (namespace, prefix) = match (&attrs.namespace, &attrs.prefix) {
+            (Some(namespace), Some(prefix)) => (quote!(#namespace), quote!(#prefix)),
+            (None, None) => default(),
+            (Some(namespace), None) => {
+                return Err(syn::Error::new(
+                    namespace.span(),
+                    "`namespace` must be used together with `prefix`",
+                ))
+            }
+            (None, Some(prefix)) => {
+                return Err(syn::Error::new(
+                    prefix.span(),
+                    "`prefix` must be used together with `namespace`",
+                ))
+            }
+        };
+        // WebDAV elements are usually lowercase without separators, e.g.
+        // `DisplayName` is `displayname`
+        let local_name = match &attrs.rename {
+            Some(rename) => rename.clone(),
+            None => {
+                let name = ident.to_string();
+                let name = name.strip_prefix("r#").unwrap_or(&name);
+                LitStr::new(&name.to_lowercase(), ident.span())
+            }
+        };
+
+        Ok(Self {
+            namespace,
+            prefix,
+            local_name,
+        })
+    }
+}
@@ -0,0 +1,471 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    spanned::Spanned, Data, DataEnum, DeriveInput, Fields, GenericArgument, Ident, PathArguments,
+    Type,
+};
+
+use crate::attr::{Attrs, Name};
+
+struct Context {
+    krate: TokenStream,
+    private: TokenStream,
+}
+
+pub(crate) fn derive_element(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = Attrs::parse(&input.attrs)?;
+    attrs.reject(&["text"], "on types, use it on a field instead")?;
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "generic elements are not supported",
+        ));
+    }
+
+    let krate = match &attrs.krate {
+        Some(krate) => krate.parse::<syn::Path>()?.into_token_stream(),
+        None => quote!(::webdav_xml),
+    };
+    let cx = Context {
+        private: quote!(#krate::__private),
+        krate,
+    };
+    let ident = &input.ident;
+    let name = Name::container(&attrs, ident, &cx.krate)?;
+
+    let Expansion {
+        markers,
+        try_from,
+        from,
+    } = match &input.data {
+        Data::Struct(data) => expand_struct(&cx, &name, &data.fields)?,
+        Data::Enum(data) => expand_enum(&cx, ident, &name, data)?,
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "unions are not supported",
+            ))
+        }
+    };
+
+    let Context { krate, .. } = &cx;
+    let element = element_impl(&cx, ident, &name);
+
+    Ok(quote! {
+        #element
+
+        const _: () = {
+            #markers
+
+            impl ::core::convert::TryFrom<&#krate::Value> for #ident {
+                type Error = #krate::ExtractElementError;
+
+                #[allow(unused_variables)]
+                fn try_from(value: &#krate::Value) -> ::core::result::Result<Self, Self::Error> {
+                    #try_from
+                }
+            }
+
+            impl ::core::convert::TryFrom<#krate::Value> for #ident {
+                type Error = #krate::ExtractElementError;
+
+                fn try_from(value: #krate::Value) -> ::core::result::Result<Self, Self::Error> {
+                    ::core::convert::TryFrom::try_from(&value)
+                }
+            }
+
+            impl ::core::convert::From<#ident> for #krate::Value {
+                #[allow(unused_variables, unused_mut)]
+                fn from(element: #ident) -> #krate::Value {
+                    #from
+                }
+            }
+        };
+    })
+}
+
+/// The bodies of the generated `TryFrom<&Value>` and `From<T> for Value`
+/// implementations (`TryFrom<Value>` delegates to `TryFrom<&Value>`), and the marker types they refer to.
+#[derive(Default)]
+struct Expansion {
+    markers: TokenStream,
+    try_from: TokenStream,
+    from: TokenStream,
+}
+
+fn expand_struct(cx: &Context, name: &Name, fields: &Fields) -> syn::Result<Expansion> {
+    let Context { krate, private } = cx;
+
+    match fields {
+        Fields::Named(fields) => {
+            let mut markers = TokenStream::new();
+            let mut extract = Vec::new();
+            let mut insert = Vec::new();
+
+            for (index, field) in fields.named.iter().enumerate() {
+                let attrs = Attrs::parse(&field.attrs)?;
+                attrs.reject(&["crate"], "on fields")?;
+                let ident = field
+                    .ident
+                    .as_ref()
+                    .expect("named fields have an identifier");
+                let (kind, ty) = field_kind(&field.ty);
+
+                let child = Child::new(cx, name, &attrs, ident, ty, kind, index, &mut markers)?;
+                let value = child.extract(cx);
+                extract.push(quote!(#ident: #value));
+                insert.push(child.insert(cx, quote!(element.#ident)));
+            }
+
+            Ok(Expansion {
+                markers,
+                try_from: quote! {
+                    let map = #private::to_map(value)?;
+                    let map = &*map;
+
+                    ::core::result::Result::Ok(Self {
+                        #(#extract,)*
+                    })
+                },
+                from: quote! {
+                    let mut map = #krate::ValueMap::new();
+
+                    #(#insert;)*
+
+                    #private::from_map(map)
+                },
+            })
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let field = &fields.unnamed[0];
+            let attrs = Attrs::parse(&field.attrs)?;
+            attrs.reject(
+                &["namespace", "prefix", "rename", "crate"],
+                "on the field of a newtype, use it on the type instead",
+            )?;
+
+            Ok(match attrs.text {
+                Some(_) => Expansion {
+                    try_from: quote!(::core::result::Result::Ok(Self(#private::parse_text(value)?))),
+                    from: quote!(#private::text(element.0)),
+                    ..Default::default()
+                },
+                None => Expansion {
+                    try_from: quote! {
+                        ::core::result::Result::Ok(Self(::core::convert::TryFrom::try_from(value)?))
+                    },
+                    from: quote!(::core::convert::Into::into(element.0)),
+                    ..Default::default()
+                },
+            })
+        }
+        Fields::Unnamed(fields) => Err(syn::Error::new(
+            fields.span(),
+            "tuple structs must have exactly one field",
+        )),
+        Fields::Unit => Ok(Expansion {
+            try_from: quote!(::core::result::Result::Ok(Self)),
+            from: quote!(#krate::Value::Empty),
+            ..Default::default()
+        }),
+    }
+}
+
+fn expand_enum(
+    cx: &Context,
+    enum_ident: &Ident,
+    name: &Name,
+    data: &DataEnum,
+) -> syn::Result<Expansion> {
+    let Context { krate, private } = cx;
+
+    if data.variants.is_empty() {
+        return Err(syn::Error::new(
+            data.enum_token.span,
+            "enums without variants are not supported",
+        ));
+    }
+
+    let mut markers = TokenStream::new();
+    let mut names = Vec::new();
+    let mut present = Vec::new();
+    let mut extract = Vec::new();
+    let mut insert = Vec::new();
+
+    for (index, variant) in data.variants.iter().enumerate() {
+        let attrs = Attrs::parse(&variant.attrs)?;
+        attrs.reject(&["crate"], "on variants")?;
+        attrs.reject(
+            &["text"],
+            "on variants, use it on the variant's field instead",
+        )?;
+        let ident = &variant.ident;
+
+        match &variant.fields {
+            Fields::Unit => {
+                let marker = format_ident!("__Child{index}");
+                let child_name = Name::child(&attrs, ident, name)?;
+                markers.extend(marker_type(cx, &marker, &child_name));
+
+                names.push(quote!(<#marker as #krate::Element>::LOCAL_NAME));
+                present.push(quote!(#private::contains::<#marker>(map)));
+                extract.push(quote!(#index => ::core::result::Result::Ok(Self::#ident)));
+                insert.push(quote! {
+                    #enum_ident::#ident => #private::insert::<#marker>(&mut map, #marker)
+                });
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let field = &fields.unnamed[0];
+                let field_attrs = Attrs::parse(&field.attrs)?;
+                field_attrs.reject(
+                    &["namespace", "prefix", "rename", "crate"],
+                    "on the field of a variant, use it on the variant instead",
+                )?;
+                // the name options of the variant apply to the text element
+                let attrs = Attrs {
+                    text: field_attrs.text,
+                    ..attrs
+                };
+
+                let child = Child::new(
+                    cx,
+                    name,
+                    &attrs,
+                    ident,
+                    &field.ty,
+                    Kind::Required,
+                    index,
+                    &mut markers,
+                )?;
+                let ty = &child.ty;
+                let value = child.extract(cx);
+
+                names.push(quote!(<#ty as #krate::Element>::LOCAL_NAME));
+                present.push(quote!(#private::contains::<#ty>(map)));
+                extract.push(quote!(#index => ::core::result::Result::Ok(Self::#ident(#value))));
+                let value = child.insert(cx, quote!(value));
+                insert.push(quote!(#enum_ident::#ident(value) => #value));
+            }
+            fields => {
+                return Err(syn::Error::new(
+                    fields.span(),
+                    "only unit variants and variants with a single unnamed field are supported",
+                ))
+            }
+        }
+    }
+
+    Ok(Expansion {
+        markers,
+        try_from: quote! {
+            let map = #private::to_map(value)?;
+            let map = &*map;
+
+            const NAMES: &[&str] = &[#(#names),*];
+
+            match #private::choose(&[#(#present),*], NAMES)? {
+                #(#extract,)*
+                _ => ::core::unreachable!(),
+            }
+        },
+        from: quote! {
+            let mut map = #krate::ValueMap::new();
+
+            match element {
+                #(#insert,)*
+            }
+
+            #krate::Value::Map(map)
+        },
+    })
+}
+
+/// How often a child element may occur.
+#[derive(Clone, Copy)]
+enum Kind {
+    Required,
+    Optional,
+    Vec,
+    NonEmpty,
+}
+
+/// Determines the kind of a field from its type, e.g. `Option<T>` is an
+/// optional child element `T`.
+///
+/// Since macros can't resolve types, this only looks at the last segment of
+/// the type's path.
+fn field_kind(ty: &Type) -> (Kind, &Type) {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let (1, Some(GenericArgument::Type(inner))) =
+                    (args.args.len(), args.args.first())
+                {
+                    let kind = match segment.ident.to_string().as_str() {
+                        "Option" => Some(Kind::Optional),
+                        "Vec" => Some(Kind::Vec),
+                        "NonEmpty" => Some(Kind::NonEmpty),
+                        _ => None,
+                    };
+                    if let Some(kind) = kind {
+                        return (kind, inner);
+                    }
+                }
+            }
+        }
+    }
+
+    (Kind::Required, ty)
+}
+
+/// A child element of a struct field or enum variant.
+struct Child {
+    kind: Kind,
+    /// The type implementing `Element` that is used to extract the child.
+    ty: TokenStream,
+    /// Whether `ty` is a `Text` wrapper around the field's type.
+    text: bool,
+}
+
+impl Child {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        cx: &Context,
+        parent: &Name,
+        attrs: &Attrs,
+        ident: &Ident,
+        ty: &Type,
+        kind: Kind,
+        index: usize,
+        markers: &mut TokenStream,
+    ) -> syn::Result<Self> {
+        let Context { private, .. } = cx;
+
+        match attrs.text {
+            Some(_) => {
+                let marker = format_ident!("__Child{index}");
+                let name = Name::child(attrs, ident, parent)?;
+                markers.extend(marker_type(cx, &marker, &name));
+
+                Ok(Self {
+                    kind,
+                    ty: quote!(#private::Text<#marker, #ty>),
+                    text: true,
+                })
+            }
+            None => {
+                attrs.reject(
+                    &["namespace", "prefix", "rename"],
+                    "without `text`, the element name is taken from the type",
+                )?;
+
+                Ok(Self {
+                    kind,
+                    ty: ty.into_token_stream(),
+                    text: false,
+                })
+            }
+        }
+    }
+
+    /// Extracts the child from the `ValueMap` called `map`.
+    fn extract(&self, cx: &Context) -> TokenStream {
+        let Context { private, .. } = cx;
+        let ty = &self.ty;
+
+        match (self.kind, self.text) {
+            (Kind::Required, false) => quote!(#private::required::<#ty>(map)?),
+            (Kind::Required, true) => quote!(#private::required::<#ty>(map)?.into_inner()),
+            (Kind::Optional, false) => quote!(#private::optional::<#ty>(map)?),
+            (Kind::Optional, true) => {
+                quote!(#private::optional::<#ty>(map)?.map(#private::Text::into_inner))
+            }
+            (Kind::Vec, false) => quote!(#private::list::<#ty>(map)?),
+            (Kind::Vec, true) => quote! {
+                #private::list::<#ty>(map)?
+                    .into_iter()
+                    .map(#private::Text::into_inner)
+                    .collect()
+            },
+            (Kind::NonEmpty, false) => quote!(#private::nonempty::<#ty>(map)?),
+            (Kind::NonEmpty, true) => {
+                quote!(#private::nonempty::<#ty>(map)?.map(#private::Text::into_inner))
+            }
+        }
+    }
+
+    /// Inserts `value` into the `ValueMap` called `map`.
+    fn insert(&self, cx: &Context, value: TokenStream) -> TokenStream {
+        let Context { private, .. } = cx;
+        let ty = &self.ty;
+
+        match (self.kind, self.text) {
+            (Kind::Required, false) => quote!(#private::insert::<#ty>(&mut map, #value)),
+            (Kind::Required, true) => {
+                quote!(#private::insert::<#ty>(&mut map, #private::Text::new(#value)))
+            }
+            (Kind::Optional, false) => quote!(#private::insert_optional::<#ty>(&mut map, #value)),
+            (Kind::Optional, true) => quote! {
+                #private::insert_optional::<#ty>(&mut map, #value.map(#private::Text::new))
+            },
+            (Kind::Vec | Kind::NonEmpty, false) => {
+                quote!(#private::insert_all::<#ty>(&mut map, #value))
+            }
+            (Kind::Vec | Kind::NonEmpty, true) => quote! {
+                #private::insert_all::<#ty>(
+                    &mut map,
+                    ::core::iter::IntoIterator::into_iter(#value).map(#private::Text::new),
+                )
+            },
+        }
+    }
+}
+
+fn element_impl(cx: &Context, ident: &Ident, name: &Name) -> TokenStream {
+    let Context { krate, .. } = cx;
+    let Name {
+        namespace,
+        prefix,
+        local_name,
+    } = name;
+
+    quote! {
+        impl #krate::Element for #ident {
+            const NAMESPACE: &'static str = #namespace;
+            const PREFIX: &'static str = #prefix;
+            const LOCAL_NAME: &'static str = #local_name;
+        }
+    }
+}
+
+/// An empty element that only exists to name a child element that doesn't
+/// have its own type, e.g. a text field or a unit variant.
+fn marker_type(cx: &Context, ident: &Ident, name: &Name) -> TokenStream {
+    let Context { krate, .. } = cx;
+    let element = element_impl(cx, ident, name);
+
+    quote! {
+        struct #ident;
+
+        #element
+
+        impl ::core::convert::TryFrom<&#krate::Value> for #ident {
+            type Error = #krate::ExtractElementError;
+
+            fn try_from(_: &#krate::Value) -> ::core::result::Result<Self, Self::Error> {
+                ::core::result::Result::Ok(Self)
+            }
+        }
+
+        impl ::core::convert::From<#ident> for #krate::Value {
+            fn from(_: #ident) -> #krate::Value {
+                #krate::Value::Empty
+            }
+        }
+    }
+}
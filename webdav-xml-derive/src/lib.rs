@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Derive macro for implementing `Element`, `TryFrom<&Value>`,
+//! `TryFrom<Value>` and `From<T> for Value` for WebDAV XML elements.
+//!
+//! This crate shouldn't be used directly, enable the `derive` feature of
+//! `webdav-xml` instead.
+
+mod attr;
+mod expand;
+
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implements `Element`, `TryFrom<&Value>`, `TryFrom<Value>` and
+/// `From<T> for Value`.
+///
+/// # Element name
+///
+/// The element is in the `DAV:` namespace and its local name is the
+/// lowercased name of the type, e.g. `DisplayName` is `displayname`. This can
+/// be changed using `#[dav(namespace = "...", prefix = "...", rename = "...")]`,
+/// where `namespace` and `prefix` must be used together.
+///
+/// If `webdav-xml` is used through another crate, e.g. `webdav-meta`, its
+/// path must be set using `#[dav(crate = "webdav_meta::xml")]`.
+///
+/// # Structs
+///
+/// - The fields of a struct with named fields are child elements. The name of
+///   a child element is taken from the field's type, which must implement
+///   `Element`, `TryFrom<&Value>` and `Into<Value>`. Fields of type
+///   `Option<T>`, `Vec<T>` or `NonEmpty<T>` are optional or may occur
+///   multiple times.
+/// - Fields marked with `#[dav(text)]` are child elements that contain text
+///   which is parsed using `FromStr` and written using `Display`. They are
+///   named after the field and can be renamed like types.
+/// - A newtype struct delegates to the field's conversions, or contains text
+///   if the field is marked with `#[dav(text)]`.
+/// - A unit struct is an empty element.
+///
+/// ```ignore
+/// use webdav_xml::Element;
+///
+/// #[derive(Element)]
+/// #[dav(namespace = "http://owncloud.org/ns", prefix = "oc", rename = "share-types")]
+/// struct ShareTypes {
+///     #[dav(text, rename = "share-type")]
+///     share_types: Vec<u32>,
+/// }
+///
+/// #[derive(Element)]
+/// #[dav(namespace = "http://owncloud.org/ns", prefix = "oc", rename = "fileid")]
+/// struct FileId(#[dav(text)] u64);
+/// ```
+///
+/// # Enums
+///
+/// Enums are elements that contain exactly one of a set of child elements.
+/// Unit variants are empty child elements that are named like types, while
+/// the child element of a variant with a single field is taken from the
+/// field's type, or is named after the variant if the field is marked with
+/// `#[dav(text)]`.
+///
+/// ```ignore
+/// #[derive(Element)]
+/// enum LockScope {
+///     Exclusive,
+///     Shared,
+/// }
+/// ```
+#[proc_macro_derive(Element, attributes(dav))]
+pub fn derive_element(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand::derive_element(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Measures the time and allocations needed to parse a `multistatus` element.
+//!
+//! Run with `cargo bench -p webdav-xml`.
+
+use divan::{black_box, AllocProfiler, Bencher};
+use webdav_xml::{elements::Multistatus, read::MultistatusReader, FromXml, Value};
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+fn main() {
+    divan::main();
+}
+
+/// A response to a `PROPFIND` request for a collection with `n` members.
+fn multistatus(n: usize) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">"#,
+    );
+    for i in 0..n {
+        xml.push_str(&format!(
+            r#"
+  <d:response>
+    <d:href>/collection/file-{i}.txt</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:displayname>file-{i}.txt</d:displayname>
+        <d:getcontentlength>{i}</d:getcontentlength>
+        <d:getcontenttype>text/plain</d:getcontenttype>
+        <d:getetag>"{i:08x}"</d:getetag>
+        <d:getlastmodified>Mon, 30 Sep 2019 12:13:02 GMT</d:getlastmodified>
+        <d:resourcetype/>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>"#
+        ));
+    }
+    xml.push_str("\n</d:multistatus>\n");
+    xml
+}
+
+#[divan::bench(args = [1, 100])]
+fn value(bencher: Bencher, n: usize) {
+    let xml = bytes::Bytes::from(multistatus(n));
+    bencher.bench(|| Value::from_xml(black_box(xml.clone())).unwrap());
+}
+
+#[divan::bench(args = [1, 100])]
+fn typed(bencher: Bencher, n: usize) {
+    let xml = bytes::Bytes::from(multistatus(n));
+    bencher.bench(|| Multistatus::from_xml(black_box(xml.clone())).unwrap());
+}
+
+#[divan::bench(args = [1, 100])]
+fn reader(bencher: Bencher, n: usize) {
+    let xml = multistatus(n);
+    bencher.bench(|| {
+        MultistatusReader::new(black_box(xml.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    });
+}
@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Helpers for the code generated by `#[derive(Element)]`. This is not part
+//! of the public API and may change at any time.
+
+use std::{borrow::Cow, fmt::Display, marker::PhantomData, str::FromStr};
+
+use nonempty::NonEmpty;
+
+use crate::{
+    element::ElementExt, utils::NonEmptyExt, Element, ExtractElementError, ExtractElementErrorKind,
+    Node, OptionExt, Value, ValueMap,
+};
+
+/// Like [`Value::to_map()`], but an empty element is an empty map and
+/// whitespace between child elements, which is kept in dead properties, is
+/// ignored.
+pub fn to_map(value: &Value) -> Result<Cow<'_, ValueMap>, ExtractElementError> {
+    if value.is_empty() {
+        return Ok(Cow::Owned(ValueMap::new()));
+    }
+    match value.formatted_elements() {
+        Some(nodes) => {
+            let mut map = ValueMap::new();
+            for node in nodes {
+                if let Node::Element(name, value) = node {
+                    map.insert_raw(name.clone(), value.clone());
+                }
+            }
+            Ok(Cow::Owned(map))
+        }
+        None => value.to_map().map(Cow::Borrowed),
+    }
+}
+
+/// Writes an empty map as an empty element.
+pub fn from_map(map: ValueMap) -> Value {
+    match map.map.is_empty() {
+        true => Value::Empty,
+        false => Value::Map(map),
+    }
+}
+
+pub fn contains<E: Element>(map: &ValueMap) -> bool {
+    map.map.contains_key(&E::element_name::<&'static str>())
+}
+
+pub fn required<'v, E>(map: &'v ValueMap) -> Result<E, ExtractElementError>
+where
+    E: Element + TryFrom<&'v Value, Error = ExtractElementError>,
+{
+    map.get().required::<E>()?
+}
+
+pub fn optional<'v, E>(map: &'v ValueMap) -> Result<Option<E>, ExtractElementError>
+where
+    E: Element + TryFrom<&'v Value, Error = ExtractElementError>,
+{
+    map.get().transpose()
+}
+
+pub fn list<'v, E>(map: &'v ValueMap) -> Result<Vec<E>, ExtractElementError>
+where
+    E: Element + TryFrom<&'v Value, Error = ExtractElementError> + 'v,
+{
+    map.iter_all().collect()
+}
+
+pub fn nonempty<'v, E>(map: &'v ValueMap) -> Result<NonEmpty<E>, ExtractElementError>
+where
+    E: Element + TryFrom<&'v Value, Error = ExtractElementError> + 'v,
+{
+    NonEmpty::try_collect(map.iter_all())?.required::<E>()
+}
+
+/// Returns the index of the only child element that is present.
+pub fn choose(
+    present: &[bool],
+    names: &'static [&'static str],
+) -> Result<usize, ExtractElementError> {
+    let mut indices = (0..present.len()).filter(|&index| present[index]);
+
+    match (indices.next(), indices.next()) {
+        (Some(index), None) => Ok(index),
+        (None, _) => Err(ExtractElementError::new(
+            ExtractElementErrorKind::MissingElement(names[0]),
+        )),
+        (Some(_), Some(_)) => Err(ExtractElementError::new(
+            ExtractElementErrorKind::ConflictingElements(names),
+        )),
+    }
+}
+
+pub fn insert<E: Element + Into<Value>>(map: &mut ValueMap, element: E) {
+    map.insert::<E>(element.into())
+}
+
+pub fn insert_optional<E: Element + Into<Value>>(map: &mut ValueMap, element: Option<E>) {
+    if let Some(element) = element {
+        insert(map, element)
+    }
+}
+
+pub fn insert_all<E: Element + Into<Value>>(
+    map: &mut ValueMap,
+    elements: impl IntoIterator<Item = E>,
+) {
+    for element in elements {
+        insert(map, element)
+    }
+}
+
+/// Parses the text content of an element, where an empty element is an
+/// empty string.
+pub fn parse_text<T>(value: &Value) -> Result<T, ExtractElementError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let text = match value.is_empty() {
+        true => "",
+        false => value.to_text()?,
+    };
+    text.parse().map_err(ExtractElementError::other)
+}
+
+pub fn text<T: Display>(value: T) -> Value {
+    let text = value.to_string();
+    match text.is_empty() {
+        true => Value::Empty,
+        false => text.into(),
+    }
+}
+
+/// A child element `E` containing text that is parsed as `T`.
+pub struct Text<E, T>(T, PhantomData<E>);
+
+impl<E, T> Text<E, T> {
+    pub fn new(value: T) -> Self {
+        Self(value, PhantomData)
+    }
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<E: Element, T> Element for Text<E, T> {
+    const NAMESPACE: &'static str = E::NAMESPACE;
+    const PREFIX: &'static str = E::PREFIX;
+    const LOCAL_NAME: &'static str = E::LOCAL_NAME;
+}
+
+impl<E, T> TryFrom<&Value> for Text<E, T>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        parse_text(value).map(Self::new)
+    }
+}
+
+impl<E, T: Display> From<Text<E, T>> for Value {
+    fn from(Text(value, _): Text<E, T>) -> Value {
+        text(value)
+    }
+}
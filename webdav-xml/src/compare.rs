@@ -0,0 +1,626 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Semantic comparison of [`Value`]s.
+//!
+//! Unlike `==`, the comparison treats values the way XML peers do:
+//!
+//! - Namespace prefixes are ignored, only namespaces matter.
+//! - Leading and trailing whitespace of text is ignored, so an element that
+//!   only contains whitespace is equal to an empty element.
+//! - The order of attributes and of sibling elements with different names is
+//!   ignored. The order of sibling elements with the same name is only
+//!   ignored when using [`CompareConfig::unordered()`].
+//!
+//! ```
+//! use webdav_xml::{compare::CompareConfig, FromXml, Value};
+//!
+//! let expected = Value::from_xml(
+//!     r#"<d:prop xmlns:d="DAV:"><d:href>/a</d:href><d:href>/b</d:href></d:prop>"#,
+//! )?;
+//! let actual = Value::from_xml(
+//!     r#"<D:prop xmlns:D="DAV:">
+//!         <D:href>/b</D:href>
+//!         <D:href> /a </D:href>
+//!     </D:prop>"#,
+//! )?;
+//!
+//! assert!(!expected.semantic_eq(&actual));
+//! assert!(CompareConfig::new().unordered(true).eq(&expected, &actual));
+//!
+//! let diff = expected.diff(&actual);
+//! assert_eq!(diff.len(), 2);
+//! assert_eq!(diff[0].to_string(), "changed prop/href[0]");
+//! # Ok::<(), webdav_xml::Error>(())
+//! ```
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{
+    value::{Attributes, Node},
+    ElementName, ElementPath, Value, ValueMap,
+};
+
+/// Options for comparing [`Value`]s, see the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct CompareConfig {
+    unordered: bool,
+}
+
+impl CompareConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Whether to ignore the order of sibling elements with the same name.
+    ///
+    /// By default, their order is significant.
+    pub fn unordered(mut self, unordered: bool) -> Self {
+        self.unordered = unordered;
+        self
+    }
+    /// Check whether two values are semantically equal.
+    ///
+    /// Unlike checking whether [`CompareConfig::diff()`] is empty, this stops
+    /// at the first difference.
+    pub fn eq(&self, a: &Value, b: &Value) -> bool {
+        if attributes(a) != attributes(b) {
+            return false;
+        }
+
+        match (Content::of(a), Content::of(b)) {
+            (Content::Empty, Content::Empty) => true,
+            (Content::Text(a), Content::Text(b)) => a == b,
+            (Content::Map(a), Content::Map(b)) => {
+                a.map.len() == b.map.len()
+                    && a.map.iter().all(|(name, a)| {
+                        b.map
+                            .get(name)
+                            .is_some_and(|b| self.siblings_eq(Siblings::of(a), Siblings::of(b)))
+                    })
+            }
+            (Content::List(a), Content::List(b)) => self.siblings_eq(a, b),
+            (Content::Mixed(a), Content::Mixed(b)) => self.nodes_eq(a, b),
+            _ => false,
+        }
+    }
+    /// List the differences between two values.
+    ///
+    /// Elements that exist in `new`, but not in `old` are reported as
+    /// [added](DifferenceKind::Added) and vice versa. Elements that exist in
+    /// both, but whose text, attributes or type of content differ are
+    /// reported as [changed](DifferenceKind::Changed).
+    pub fn diff<'v>(&self, old: &'v Value, new: &'v Value) -> Vec<Difference<'v>> {
+        let mut differences = Vec::new();
+        self.diff_value(&ElementPath::default(), old, new, &mut differences);
+        differences
+    }
+
+    fn diff_value<'v>(
+        &self,
+        path: &ElementPath,
+        old: &'v Value,
+        new: &'v Value,
+        differences: &mut Vec<Difference<'v>>,
+    ) {
+        let changed = || Difference {
+            path: path.clone(),
+            kind: DifferenceKind::Changed { old, new },
+        };
+
+        if attributes(old) != attributes(new) {
+            return differences.push(changed());
+        }
+
+        match (Content::of(old), Content::of(new)) {
+            (Content::Map(old), Content::Map(new)) => self.diff_map(path, old, new, differences),
+            (Content::List(old_items), Content::List(new_items)) => self.diff_items(
+                path,
+                None,
+                old_items.iter().collect(),
+                new_items.iter().collect(),
+                differences,
+            ),
+            (Content::Mixed(old), Content::Mixed(new)) if self.nodes_eq(old, new) => (),
+            (Content::Text(old), Content::Text(new)) if old == new => (),
+            (Content::Empty, Content::Empty) => (),
+            _ => differences.push(changed()),
+        }
+    }
+
+    fn diff_map<'v>(
+        &self,
+        path: &ElementPath,
+        old: &'v ValueMap,
+        new: &'v ValueMap,
+        differences: &mut Vec<Difference<'v>>,
+    ) {
+        for (name, old_value) in &old.map {
+            let new_items = new.map.get(name).map_or_else(Vec::new, items);
+            self.diff_items(path, Some(name), items(old_value), new_items, differences);
+        }
+        for (name, new_value) in &new.map {
+            if !old.map.contains_key(name) {
+                self.diff_items(path, Some(name), Vec::new(), items(new_value), differences);
+            }
+        }
+    }
+
+    /// Compares the elements called `name` in `old` and `new`, or the items
+    /// of a list if `name` is `None`.
+    fn diff_items<'v>(
+        &self,
+        path: &ElementPath,
+        name: Option<&ElementName<bytestring::ByteString>>,
+        old: Vec<&'v Value>,
+        new: Vec<&'v Value>,
+        differences: &mut Vec<Difference<'v>>,
+    ) {
+        let path_of = |index: usize| {
+            let mut path = path.clone();
+            if let Some(name) = name {
+                // like for `XmlError`s, any element except the root may be repeated
+                path.push(name.clone(), (!path.is_empty()).then_some(index));
+            }
+            path
+        };
+
+        // pairs of indices into `old` and `new` that are compared
+        let mut pairs = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+
+        if self.unordered {
+            let mut buckets = self.buckets(&new);
+            for (i, old) in old.iter().enumerate() {
+                if self.take_equal(&mut buckets, &new, old).is_none() {
+                    removed.push(i);
+                }
+            }
+            let mut unmatched: Vec<usize> = buckets.into_values().flatten().collect();
+            unmatched.sort_unstable();
+            // elements without an equal counterpart are compared in order
+            let paired = removed.len().min(unmatched.len());
+            pairs.extend(removed.drain(..paired).zip(unmatched.drain(..paired)));
+            added = unmatched;
+        } else {
+            let paired = old.len().min(new.len());
+            pairs.extend((0..paired).map(|i| (i, i)));
+            removed.extend(paired..old.len());
+            added.extend(paired..new.len());
+        }
+
+        for (i, j) in pairs {
+            self.diff_value(&path_of(i), old[i], new[j], differences);
+        }
+        differences.extend(removed.into_iter().map(|i| Difference {
+            path: path_of(i),
+            kind: DifferenceKind::Removed(old[i]),
+        }));
+        differences.extend(added.into_iter().map(|j| Difference {
+            path: path_of(j),
+            kind: DifferenceKind::Added(new[j]),
+        }));
+    }
+
+    fn siblings_eq(&self, a: Siblings<'_>, b: Siblings<'_>) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        if !self.unordered || a.len() == 1 {
+            return a.iter().zip(b.iter()).all(|(a, b)| self.eq(a, b));
+        }
+
+        let b: Vec<_> = b.iter().collect();
+        let mut buckets = self.buckets(&b);
+        a.iter()
+            .all(|a| self.take_equal(&mut buckets, &b, a).is_some())
+    }
+
+    fn nodes_eq(&self, old: &[Node], new: &[Node]) -> bool {
+        let mut new = mixed_nodes(new);
+        mixed_nodes(old).all(|old| match (old, new.next()) {
+            (MixedNode::Text(old), Some(MixedNode::Text(new))) => old == new,
+            (MixedNode::Element(old_name, old), Some(MixedNode::Element(new_name, new))) => {
+                old_name == new_name && self.eq(old, new)
+            }
+            _ => false,
+        }) && new.next().is_none()
+    }
+
+    /// Groups the indices of `values` by the [hash](CompareConfig::hash()) of
+    /// the values, in ascending order.
+    fn buckets(&self, values: &[&Value]) -> HashMap<u64, Vec<usize>> {
+        let mut buckets = HashMap::<_, Vec<_>>::new();
+        for (index, value) in values.iter().enumerate() {
+            buckets.entry(self.hash(value)).or_default().push(index);
+        }
+        buckets
+    }
+
+    /// Removes the first index from `buckets` whose value in `values` is
+    /// equal to `value`.
+    fn take_equal(
+        &self,
+        buckets: &mut HashMap<u64, Vec<usize>>,
+        values: &[&Value],
+        value: &Value,
+    ) -> Option<usize> {
+        let bucket = buckets.get_mut(&self.hash(value))?;
+        let position = bucket
+            .iter()
+            .position(|&index| self.eq(value, values[index]))?;
+        Some(bucket.remove(position))
+    }
+
+    /// A hash that is the same for all values that are equal according to
+    /// [`CompareConfig::eq()`].
+    fn hash(&self, value: &Value) -> u64 {
+        let mut state = DefaultHasher::new();
+        self.hash_value(value, &mut state);
+        state.finish()
+    }
+
+    fn hash_value(&self, value: &Value, state: &mut DefaultHasher) {
+        // the order of attributes and of elements with different names is
+        // ignored, so their hashes are combined using a commutative operation
+        if let Some(attributes) = attributes(value) {
+            let hash = attributes
+                .iter()
+                .map(|attribute| hash_of(&attribute))
+                .fold(0, u64::wrapping_add);
+            state.write_u64(hash);
+        }
+
+        match Content::of(value) {
+            Content::Empty => state.write_u8(0),
+            Content::Text(text) => {
+                state.write_u8(1);
+                text.hash(state);
+            }
+            Content::Map(map) => {
+                state.write_u8(2);
+                let hash = map
+                    .map
+                    .iter()
+                    .map(|(name, value)| {
+                        let mut state = DefaultHasher::new();
+                        name.hash(&mut state);
+                        self.hash_siblings(Siblings::of(value), &mut state);
+                        state.finish()
+                    })
+                    .fold(0, u64::wrapping_add);
+                state.write_u64(hash);
+            }
+            Content::List(items) => {
+                state.write_u8(3);
+                self.hash_siblings(items, state);
+            }
+            Content::Mixed(nodes) => {
+                state.write_u8(4);
+                for node in mixed_nodes(nodes) {
+                    match node {
+                        MixedNode::Text(text) => text.hash(state),
+                        MixedNode::Element(name, value) => {
+                            name.hash(state);
+                            self.hash_value(value, state);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn hash_siblings(&self, siblings: Siblings<'_>, state: &mut DefaultHasher) {
+        state.write_usize(siblings.len());
+        match self.unordered {
+            true => state.write_u64(
+                siblings
+                    .iter()
+                    .map(|value| self.hash(value))
+                    .fold(0, u64::wrapping_add),
+            ),
+            false => siblings
+                .iter()
+                .for_each(|value| self.hash_value(value, state)),
+        }
+    }
+}
+
+impl Value {
+    /// Check whether two values are semantically equal using the default
+    /// [`CompareConfig`].
+    pub fn semantic_eq(&self, other: &Value) -> bool {
+        CompareConfig::new().eq(self, other)
+    }
+    /// List the differences to another value using the default
+    /// [`CompareConfig`].
+    pub fn diff<'v>(&'v self, new: &'v Value) -> Vec<Difference<'v>> {
+        CompareConfig::new().diff(self, new)
+    }
+}
+
+/// A difference between two [`Value`]s, see [`CompareConfig::diff()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference<'v> {
+    /// The path of the element that differs, relative to the compared values.
+    pub path: ElementPath,
+    pub kind: DifferenceKind<'v>,
+}
+
+impl std::fmt::Display for Difference<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            DifferenceKind::Added(_) => "added",
+            DifferenceKind::Removed(_) => "removed",
+            DifferenceKind::Changed { .. } => "changed",
+        };
+        match self.path.is_empty() {
+            true => write!(f, "{kind} value"),
+            false => write!(f, "{kind} {}", self.path),
+        }
+    }
+}
+
+/// The kind of a [`Difference`], with the content of the affected element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DifferenceKind<'v> {
+    Added(&'v Value),
+    Removed(&'v Value),
+    Changed { old: &'v Value, new: &'v Value },
+}
+
+/// The content of a value, with insignificant whitespace removed.
+enum Content<'v> {
+    Empty,
+    Text(&'v str),
+    Map(&'v ValueMap),
+    List(Siblings<'v>),
+    /// Contains at least one element or multiple text nodes
+    Mixed(&'v [Node]),
+}
+
+enum MixedNode<'v> {
+    Text(&'v str),
+    Element(&'v ElementName<bytestring::ByteString>, &'v Value),
+}
+
+impl<'v> Content<'v> {
+    fn of(value: &'v Value) -> Self {
+        match value.without_attributes() {
+            Value::Empty => Self::Empty,
+            Value::Text(text) => match text.trim() {
+                "" => Self::Empty,
+                text => Self::Text(text),
+            },
+            Value::Map(map) if map.map.is_empty() => Self::Empty,
+            Value::Map(map) => Self::Map(map),
+            Value::List(_) => Self::List(Siblings::of(value)),
+            Value::Mixed(nodes) => {
+                let mut iter = mixed_nodes(nodes);
+                match (iter.next(), iter.next()) {
+                    (None, _) => Self::Empty,
+                    (Some(MixedNode::Text(text)), None) => Self::Text(text),
+                    _ => Self::Mixed(nodes),
+                }
+            }
+            Value::WithAttributes { .. } => unreachable!("attributes were removed"),
+        }
+    }
+}
+
+fn attributes(value: &Value) -> Option<&Attributes> {
+    value
+        .attributes()
+        .filter(|attributes| !attributes.is_empty())
+}
+
+/// The nodes of mixed content, without insignificant whitespace.
+fn mixed_nodes(nodes: &[Node]) -> impl Iterator<Item = MixedNode<'_>> {
+    nodes.iter().filter_map(|node| match node {
+        Node::Text(text) => match text.trim() {
+            "" => None,
+            text => Some(MixedNode::Text(text)),
+        },
+        Node::Element(name, value) => Some(MixedNode::Element(name, value)),
+    })
+}
+
+/// The elements with the same name in a [`ValueMap`], or the items of a
+/// list.
+#[derive(Clone, Copy)]
+struct Siblings<'v> {
+    head: &'v Value,
+    tail: &'v [Value],
+}
+
+impl<'v> Siblings<'v> {
+    fn of(value: &'v Value) -> Self {
+        match value {
+            Value::List(list) => Self {
+                head: &list.head,
+                tail: &list.tail,
+            },
+            value => Self {
+                head: value,
+                tail: &[],
+            },
+        }
+    }
+    fn len(self) -> usize {
+        1 + self.tail.len()
+    }
+    fn iter(self) -> impl Iterator<Item = &'v Value> {
+        std::iter::once(self.head).chain(self.tail)
+    }
+}
+
+fn items(value: &Value) -> Vec<&Value> {
+    Siblings::of(value).iter().collect()
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut state = DefaultHasher::new();
+    value.hash(&mut state);
+    state.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::FromXml;
+
+    fn diff(
+        config: CompareConfig,
+        old: &'static str,
+        new: &'static str,
+    ) -> eyre::Result<Vec<String>> {
+        let old = Value::from_xml(old)?;
+        let new = Value::from_xml(new)?;
+        Ok(config
+            .diff(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    #[test]
+    fn semantic_eq() -> eyre::Result<()> {
+        let a = Value::from_xml(
+            r#"<d:prop xmlns:d="DAV:" xmlns:x="urn:x"><d:displayname>a</d:displayname><x:color x:c="1" b="2"/><d:getetag/></d:prop>"#,
+        )?;
+        let b = Value::from_xml(
+            r#"
+            <prop xmlns="DAV:" xmlns:y="urn:x">
+                <getetag>  </getetag>
+                <y:color b="2" y:c="1"/>
+                <displayname>
+                    a
+                </displayname>
+            </prop>
+            "#,
+        )?;
+        assert!(a != b);
+        assert!(a.semantic_eq(&b));
+        assert_eq!(a.diff(&b), []);
+
+        let c = Value::from_xml(r#"<prop xmlns="DAV:"><displayname>a</displayname></prop>"#)?;
+        assert!(!a.semantic_eq(&c));
+
+        Ok(())
+    }
+
+    #[test]
+    fn mixed() -> eyre::Result<()> {
+        let a = Value::from_xml("<p>a <b>b</b></p>")?;
+        let b = Value::from_xml("<p> a<b> b </b>\n</p>")?;
+        let c = Value::from_xml("<p><b>b</b> a</p>")?;
+        assert!(a.semantic_eq(&b));
+        assert!(!a.semantic_eq(&c));
+        assert!(!CompareConfig::new().unordered(true).eq(&a, &c));
+
+        Ok(())
+    }
+
+    #[test]
+    fn diff_paths() -> eyre::Result<()> {
+        let old = r#"
+        <d:multistatus xmlns:d="DAV:">
+            <d:response><d:href>/a</d:href></d:response>
+            <d:response><d:href>/b</d:href><d:status>HTTP/1.1 200 OK</d:status></d:response>
+            <d:responsedescription>old</d:responsedescription>
+        </d:multistatus>
+        "#;
+        let new = r#"
+        <d:multistatus xmlns:d="DAV:">
+            <d:response><d:href>/a</d:href></d:response>
+            <d:response><d:href>/c</d:href></d:response>
+            <d:response><d:href>/d</d:href></d:response>
+            <d:sync-token>1</d:sync-token>
+        </d:multistatus>
+        "#;
+
+        assert_eq!(
+            diff(CompareConfig::new(), old, new)?,
+            [
+                "changed multistatus/response[1]/href[0]",
+                "removed multistatus/response[1]/status[0]",
+                "added multistatus/response[2]",
+                "removed multistatus/responsedescription[0]",
+                "added multistatus/sync-token[0]",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unordered() -> eyre::Result<()> {
+        let old = r#"<r><i>1</i><i>2</i><i>3</i></r>"#;
+        let new = r#"<r><i>3</i><i>1</i><i>4</i><i>5</i></r>"#;
+
+        assert_eq!(
+            diff(CompareConfig::new(), old, new)?,
+            [
+                "changed r/i[0]",
+                "changed r/i[1]",
+                "changed r/i[2]",
+                "added r/i[3]",
+            ]
+        );
+        assert_eq!(
+            diff(CompareConfig::new().unordered(true), old, new)?,
+            ["changed r/i[1]", "added r/i[3]"]
+        );
+
+        let old = Value::from_xml(old)?;
+        let new = Value::from_xml(r#"<r><i>3</i><i>1</i><i>2</i></r>"#)?;
+        assert!(!old.semantic_eq(&new));
+        assert!(CompareConfig::new().unordered(true).eq(&old, &new));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unordered_nested() -> eyre::Result<()> {
+        /// Nested `i` elements whose children are in reverse order if
+        /// `reverse` is true.
+        fn tree(depth: usize, id: &str, reverse: bool) -> String {
+            if depth == 0 {
+                return format!("<i>{id}</i>");
+            }
+            let mut children: Vec<_> = (0..8)
+                .map(|n| tree(depth - 1, &format!("{id}.{n}"), reverse))
+                .collect();
+            if reverse {
+                children.reverse();
+            }
+            format!("<i><id>{id}</id>{}</i>", children.concat())
+        }
+
+        let old = Value::from_xml(tree(4, "0", false))?;
+        let new = Value::from_xml(tree(4, "0", true))?;
+        let config = CompareConfig::new().unordered(true);
+        assert!(!old.semantic_eq(&new));
+        assert!(config.eq(&old, &new));
+        assert_eq!(config.diff(&old, &new), []);
+
+        let changed = Value::from_xml(tree(4, "0", true).replace("<i>0.7.0.3.1</i>", "<i>x</i>"))?;
+        assert!(!config.eq(&old, &changed));
+        assert_eq!(
+            config
+                .diff(&old, &changed)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["changed i/i[7]/i[0]/i[3]/i[1]"]
+        );
+
+        Ok(())
+    }
+}
@@ -35,6 +35,13 @@ pub struct ElementName<S = &'static str> {
     pub local_name: S,
 }
 
+impl ElementName<bytestring::ByteString> {
+    /// Check if this name refers to the element `E`.
+    pub(crate) fn is<E: Element>(&self) -> bool {
+        self.namespace.as_deref() == Some(E::NAMESPACE) && self.local_name == E::LOCAL_NAME
+    }
+}
+
 impl indexmap::Equivalent<ElementName<bytestring::ByteString>> for ElementName<&str> {
     fn equivalent(&self, key: &ElementName<bytestring::ByteString>) -> bool {
         self.namespace == key.namespace.as_deref() && self.local_name == &*key.local_name
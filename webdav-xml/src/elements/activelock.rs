@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    elements::{Depth, LockRoot, LockScope, LockToken, LockType, Owner, Timeout},
+    value::ValueMap,
+    Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `activelock` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_activelock).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveLock {
+    pub lockscope: LockScope,
+    pub locktype: LockType,
+    pub depth: Depth,
+    pub owner: Option<Owner>,
+    pub timeout: Option<Timeout>,
+    pub locktoken: Option<LockToken>,
+    pub lockroot: LockRoot,
+}
+
+impl Element for ActiveLock {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "activelock";
+}
+
+impl TryFrom<&Value> for ActiveLock {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self {
+            lockscope: map.get().required::<LockScope>()??,
+            locktype: map.get().required::<LockType>()??,
+            depth: map.get().required::<Depth>()??,
+            owner: map.get().transpose()?,
+            timeout: map.get().transpose()?,
+            locktoken: map.get().transpose()?,
+            lockroot: map.get().required::<LockRoot>()??,
+        })
+    }
+}
+
+crate::utils::owned_try_from!(ActiveLock);
+
+impl From<ActiveLock> for Value {
+    fn from(
+        ActiveLock {
+            lockscope,
+            locktype,
+            depth,
+            owner,
+            timeout,
+            locktoken,
+            lockroot,
+        }: ActiveLock,
+    ) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<LockScope>(lockscope.into());
+        map.insert::<LockType>(locktype.into());
+        map.insert::<Depth>(depth.into());
+        if let Some(owner) = owner {
+            map.insert::<Owner>(owner.into());
+        }
+        if let Some(timeout) = timeout {
+            map.insert::<Timeout>(timeout.into());
+        }
+        if let Some(locktoken) = locktoken {
+            map.insert::<LockToken>(locktoken.into());
+        }
+        map.insert::<LockRoot>(lockroot.into());
+
+        Value::Map(map)
+    }
+}
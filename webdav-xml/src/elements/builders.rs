@@ -0,0 +1,375 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytestring::ByteString;
+use http::StatusCode;
+use nonempty::{nonempty, NonEmpty};
+
+use crate::{
+    element::{Element, ElementExt, ElementName},
+    elements::{Error, Href, Multistatus, Properties, Propstat, Response, Status},
+};
+
+/// Builds the [`Response`] for a `PROPPATCH` request from the outcome of each
+/// property instruction.
+///
+/// As defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#rfc.section.9.2),
+/// `PROPPATCH` is atomic: if any property fails, the properties that
+/// succeeded on their own are reported as `424 Failed Dependency`.
+///
+/// Each property is reported once: recording a property again replaces its
+/// previous outcome.
+#[derive(Clone, Debug)]
+pub struct ProppatchResponseBuilder {
+    href: Href,
+    succeeded: Vec<ElementName<ByteString>>,
+    failed: Vec<(Status, Option<Error>, Vec<ElementName<ByteString>>)>,
+}
+
+impl ProppatchResponseBuilder {
+    pub fn new(href: impl Into<Href>) -> Self {
+        Self {
+            href: href.into(),
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+    /// Record that the property could be set or removed.
+    pub fn ok<P: Element>(self) -> Self {
+        self.ok_raw(P::element_name())
+    }
+    /// Like [`ProppatchResponseBuilder::ok()`], but for properties without a
+    /// typed representation.
+    pub fn ok_raw(mut self, name: ElementName<ByteString>) -> Self {
+        if !self.succeeded.contains(&name) {
+            self.remove(&name);
+            self.succeeded.push(name);
+        }
+        self
+    }
+    /// Record that the property couldn't be set or removed.
+    pub fn failed<P: Element>(self, status: StatusCode) -> Self {
+        self.failed_raw(P::element_name(), status)
+    }
+    /// Like [`ProppatchResponseBuilder::failed()`], but for properties without
+    /// a typed representation.
+    pub fn failed_raw(self, name: ElementName<ByteString>, status: StatusCode) -> Self {
+        self.insert_failed(name, status, None)
+    }
+    /// Record that the property couldn't be set or removed because a
+    /// precondition or postcondition was violated.
+    pub fn failed_with_error<P: Element>(
+        self,
+        status: StatusCode,
+        error: impl Into<Error>,
+    ) -> Self {
+        self.failed_with_error_raw(P::element_name(), status, error)
+    }
+    /// Like [`ProppatchResponseBuilder::failed_with_error()`], but for
+    /// properties without a typed representation.
+    pub fn failed_with_error_raw(
+        self,
+        name: ElementName<ByteString>,
+        status: StatusCode,
+        error: impl Into<Error>,
+    ) -> Self {
+        self.insert_failed(name, status, Some(error.into()))
+    }
+    /// Group the properties by their status and create the response.
+    pub fn build(self) -> Response {
+        let Self {
+            href,
+            succeeded,
+            failed,
+        } = self;
+
+        fn prop(names: Vec<ElementName<ByteString>>) -> Properties {
+            let mut prop = Properties::new();
+            for name in names {
+                prop.insert_name(name);
+            }
+            prop
+        }
+
+        let mut propstat = failed
+            .into_iter()
+            .filter(|(_, _, names)| !names.is_empty())
+            .map(|(status, error, names)| Propstat {
+                prop: prop(names),
+                status,
+                error,
+                responsedescription: None,
+            })
+            .collect::<Vec<_>>();
+        let status = if propstat.is_empty() {
+            StatusCode::OK
+        } else {
+            StatusCode::FAILED_DEPENDENCY
+        };
+        if !succeeded.is_empty() || propstat.is_empty() {
+            propstat.push(Propstat {
+                prop: prop(succeeded),
+                status: Status(status),
+                error: None,
+                responsedescription: None,
+            });
+        }
+
+        Response::Propstat {
+            href,
+            propstat: NonEmpty::from_vec(propstat)
+                .expect("at least one `propstat` element is always added"),
+            error: None,
+            responsedescription: None,
+            location: None,
+        }
+    }
+
+    fn insert_failed(
+        mut self,
+        name: ElementName<ByteString>,
+        status: StatusCode,
+        error: Option<Error>,
+    ) -> Self {
+        let status = Status(status);
+
+        match self
+            .failed
+            .iter()
+            .position(|(s, e, _)| s == &status && e == &error)
+        {
+            Some(index) if self.failed[index].2.contains(&name) => (),
+            Some(index) => {
+                self.remove(&name);
+                self.failed[index].2.push(name);
+            }
+            None => {
+                self.remove(&name);
+                self.failed.push((status, error, vec![name]));
+            }
+        }
+
+        self
+    }
+    /// Remove the previously recorded outcome of a property.
+    fn remove(&mut self, name: &ElementName<ByteString>) {
+        self.succeeded.retain(|n| n != name);
+        for (_, _, names) in &mut self.failed {
+            names.retain(|n| n != name);
+        }
+    }
+}
+
+/// Builds a [`Multistatus`] for a `DELETE`, `COPY` or `MOVE` request on a
+/// collection from the members that couldn't be processed.
+///
+/// Members with the same status and error are combined into a single
+/// [`Response::Status`]. Successfully processed members must not be reported,
+/// so if [`MemberStatusBuilder::is_empty()`] returns `true`, the server should
+/// respond with a plain status code instead.
+#[derive(Clone, Debug, Default)]
+pub struct MemberStatusBuilder {
+    failed: Vec<(Status, Option<Error>, NonEmpty<Href>)>,
+}
+
+impl MemberStatusBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Record that the member couldn't be processed.
+    pub fn failed(self, href: impl Into<Href>, status: StatusCode) -> Self {
+        self.insert_failed(href.into(), status, None)
+    }
+    /// Record that the member couldn't be processed because a precondition or
+    /// postcondition was violated.
+    pub fn failed_with_error(
+        self,
+        href: impl Into<Href>,
+        status: StatusCode,
+        error: impl Into<Error>,
+    ) -> Self {
+        self.insert_failed(href.into(), status, Some(error.into()))
+    }
+    /// Returns `true` if no failures have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.failed.is_empty()
+    }
+    /// Create the multistatus response.
+    pub fn build(self) -> Multistatus {
+        Multistatus {
+            response: self
+                .failed
+                .into_iter()
+                .map(|(status, error, href)| Response::Status {
+                    href,
+                    status,
+                    error,
+                    responsedescription: None,
+                    location: None,
+                })
+                .collect(),
+            responsedescription: None,
+        }
+    }
+
+    fn insert_failed(mut self, href: Href, status: StatusCode, error: Option<Error>) -> Self {
+        let status = Status(status);
+
+        match self
+            .failed
+            .iter_mut()
+            .find(|(s, e, _)| s == &status && e == &error)
+        {
+            Some((_, _, hrefs)) => hrefs.push(href),
+            None => self.failed.push((status, error, nonempty![href])),
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        elements::Condition,
+        properties::{ContentLanguage, DisplayName, ResourceType},
+        utils::test_serialize,
+    };
+
+    use super::*;
+
+    #[test]
+    fn proppatch_ok() -> eyre::Result<()> {
+        let response = ProppatchResponseBuilder::new(Href("/bar.html".parse()?))
+            .ok::<DisplayName>()
+            .ok::<ContentLanguage>()
+            .ok::<DisplayName>()
+            .build();
+
+        test_serialize(
+            r#"
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/bar.html</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:displayname/>
+        <d:getcontentlanguage/>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>
+            "#,
+            Multistatus {
+                response: vec![response],
+                responsedescription: None,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn proppatch_failed_dependency() -> eyre::Result<()> {
+        let color = ElementName {
+            namespace: Some("http://example.com/ns".into()),
+            prefix: Some("x".into()),
+            local_name: "color".into(),
+        };
+        let response = ProppatchResponseBuilder::new(Href("/bar.html".parse()?))
+            .ok::<DisplayName>()
+            .ok::<ResourceType>()
+            .failed_with_error::<ResourceType>(
+                StatusCode::FORBIDDEN,
+                Condition::CannotModifyProtectedProperty,
+            )
+            .failed::<ContentLanguage>(StatusCode::CONFLICT)
+            .ok_raw(color.clone())
+            .failed_raw(color, StatusCode::CONFLICT)
+            .build();
+
+        test_serialize(
+            r#"
+<d:multistatus xmlns:d="DAV:" xmlns:x="http://example.com/ns">
+  <d:response>
+    <d:href>/bar.html</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:resourcetype/>
+      </d:prop>
+      <d:status>HTTP/1.1 403 Forbidden</d:status>
+      <d:error>
+        <d:cannot-modify-protected-property/>
+      </d:error>
+    </d:propstat>
+    <d:propstat>
+      <d:prop>
+        <d:getcontentlanguage/>
+        <x:color/>
+      </d:prop>
+      <d:status>HTTP/1.1 409 Conflict</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop>
+        <d:displayname/>
+      </d:prop>
+      <d:status>HTTP/1.1 424 Failed Dependency</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>
+            "#,
+            Multistatus {
+                response: vec![response],
+                responsedescription: None,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn member_status() -> eyre::Result<()> {
+        let builder = MemberStatusBuilder::new();
+        assert!(builder.is_empty());
+
+        let multistatus = builder
+            .failed_with_error(
+                Href("/container/resource1".parse()?),
+                StatusCode::LOCKED,
+                Condition::LockTokenSubmitted(vec![Href("/container/".parse()?)]),
+            )
+            .failed(Href("/container/resource2".parse()?), StatusCode::FORBIDDEN)
+            .failed_with_error(
+                Href("/container/resource3".parse()?),
+                StatusCode::LOCKED,
+                Condition::LockTokenSubmitted(vec![Href("/container/".parse()?)]),
+            )
+            .build();
+
+        test_serialize(
+            r#"
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/container/resource1</d:href>
+    <d:href>/container/resource3</d:href>
+    <d:status>HTTP/1.1 423 Locked</d:status>
+    <d:error>
+      <d:lock-token-submitted>
+        <d:href>/container/</d:href>
+      </d:lock-token-submitted>
+    </d:error>
+  </d:response>
+  <d:response>
+    <d:href>/container/resource2</d:href>
+    <d:status>HTTP/1.1 403 Forbidden</d:status>
+  </d:response>
+</d:multistatus>
+            "#,
+            multistatus,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Element, ExtractElementError, Value, DAV_NAMESPACE, DAV_PREFIX};
+
+/// The `depth` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_depth).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Depth {
+    Zero,
+    One,
+    Infinity,
+}
+
+impl Element for Depth {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "depth";
+}
+
+impl FromStr for Depth {
+    type Err = InvalidDepth;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Self::Zero),
+            "1" => Ok(Self::One),
+            s if s.eq_ignore_ascii_case("infinity") => Ok(Self::Infinity),
+            _ => Err(InvalidDepth(s.into())),
+        }
+    }
+}
+
+impl Display for Depth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Zero => "0",
+            Self::One => "1",
+            Self::Infinity => "infinity",
+        })
+    }
+}
+
+impl TryFrom<&Value> for Depth {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.to_text()?.parse() {
+            Ok(depth) => Ok(depth),
+            Err(e) => Err(ExtractElementError::other(e)),
+        }
+    }
+}
+
+crate::utils::owned_try_from!(Depth);
+
+impl From<Depth> for Value {
+    fn from(depth: Depth) -> Value {
+        depth.to_string().into()
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidDepth(String);
+
+impl std::fmt::Display for InvalidDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid depth: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidDepth {}
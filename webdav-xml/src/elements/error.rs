@@ -0,0 +1,249 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytestring::ByteString;
+use nonempty::NonEmpty;
+
+use crate::{
+    element::ElementName, elements::Href, value::ValueMap, Element, ExtractElementError, Value,
+    DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `error` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_error).
+///
+/// Contains the precondition or postcondition codes that a request violated.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Error(pub Vec<Condition>);
+
+/// A precondition or postcondition code as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#precondition.postcondition.xml.elements).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// `lock-token-matches-request-uri`
+    LockTokenMatchesRequestUri,
+    /// `lock-token-submitted`, containing the URLs of the locked resources
+    LockTokenSubmitted(Vec<Href>),
+    /// `no-conflicting-lock`, optionally containing the root of the
+    /// conflicting lock
+    NoConflictingLock(Vec<Href>),
+    /// `no-external-entities`
+    NoExternalEntities,
+    /// `preserved-live-properties`
+    PreservedLiveProperties,
+    /// `propfind-finite-depth`
+    PropfindFiniteDepth,
+    /// `cannot-modify-protected-property`
+    CannotModifyProtectedProperty,
+    /// A condition code that isn't defined in RFC 4918.
+    Other(ElementName<ByteString>, Value),
+}
+
+impl Element for Error {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "error";
+}
+
+impl TryFrom<&Value> for Error {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        // RFC 4918 requires at least one `href` in `lock-token-submitted`, but
+        // its own examples omit it
+        fn hrefs(value: &Value) -> Result<Vec<Href>, ExtractElementError> {
+            match value.without_attributes() {
+                Value::Empty => Ok(Vec::new()),
+                value => value.to_map()?.iter_all().collect(),
+            }
+        }
+
+        let map = match value.without_attributes() {
+            Value::Empty => return Ok(Self::default()),
+            value => value.to_map()?,
+        };
+
+        map.iter()
+            .map(|(name, value)| {
+                Ok(if name.is::<LockTokenMatchesRequestUri>() {
+                    Condition::LockTokenMatchesRequestUri
+                } else if name.is::<LockTokenSubmitted>() {
+                    Condition::LockTokenSubmitted(
+                        hrefs(value).map_err(|e| e.within(name.clone(), None))?,
+                    )
+                } else if name.is::<NoConflictingLock>() {
+                    Condition::NoConflictingLock(
+                        hrefs(value).map_err(|e| e.within(name.clone(), None))?,
+                    )
+                } else if name.is::<NoExternalEntities>() {
+                    Condition::NoExternalEntities
+                } else if name.is::<PreservedLiveProperties>() {
+                    Condition::PreservedLiveProperties
+                } else if name.is::<PropfindFiniteDepth>() {
+                    Condition::PropfindFiniteDepth
+                } else if name.is::<CannotModifyProtectedProperty>() {
+                    Condition::CannotModifyProtectedProperty
+                } else {
+                    Condition::Other(name.clone(), value.clone())
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+crate::utils::owned_try_from!(Error);
+
+impl From<Error> for Value {
+    fn from(Error(conditions): Error) -> Value {
+        fn hrefs(hrefs: impl IntoIterator<Item = Href>) -> Value {
+            match NonEmpty::collect(hrefs.into_iter().map(Value::from)) {
+                Some(hrefs) => {
+                    let mut map = ValueMap::new();
+                    map.insert::<Href>(Value::List(Box::new(hrefs)));
+                    Value::Map(map)
+                }
+                None => Value::Empty,
+            }
+        }
+
+        let mut map = ValueMap::new();
+
+        for condition in conditions {
+            match condition {
+                Condition::LockTokenMatchesRequestUri => {
+                    map.insert::<LockTokenMatchesRequestUri>(Value::Empty)
+                }
+                Condition::LockTokenSubmitted(href) => {
+                    map.insert::<LockTokenSubmitted>(hrefs(href))
+                }
+                Condition::NoConflictingLock(href) => map.insert::<NoConflictingLock>(hrefs(href)),
+                Condition::NoExternalEntities => map.insert::<NoExternalEntities>(Value::Empty),
+                Condition::PreservedLiveProperties => {
+                    map.insert::<PreservedLiveProperties>(Value::Empty)
+                }
+                Condition::PropfindFiniteDepth => map.insert::<PropfindFiniteDepth>(Value::Empty),
+                Condition::CannotModifyProtectedProperty => {
+                    map.insert::<CannotModifyProtectedProperty>(Value::Empty)
+                }
+                Condition::Other(name, value) => map.insert_raw(name, value),
+            }
+        }
+
+        if map.as_ref().is_empty() {
+            Value::Empty
+        } else {
+            Value::Map(map)
+        }
+    }
+}
+
+impl From<Condition> for Error {
+    fn from(condition: Condition) -> Self {
+        Error(vec![condition])
+    }
+}
+
+macro_rules! condition_elements {
+    ($($ident:ident => $local_name:literal),* $(,)?) => {
+        $(
+            struct $ident;
+
+            impl Element for $ident {
+                const NAMESPACE: &'static str = DAV_NAMESPACE;
+                const PREFIX: &'static str = DAV_PREFIX;
+                const LOCAL_NAME: &'static str = $local_name;
+            }
+        )*
+    };
+}
+
+condition_elements! {
+    LockTokenMatchesRequestUri => "lock-token-matches-request-uri",
+    LockTokenSubmitted => "lock-token-submitted",
+    NoConflictingLock => "no-conflicting-lock",
+    NoExternalEntities => "no-external-entities",
+    PreservedLiveProperties => "preserved-live-properties",
+    PropfindFiniteDepth => "propfind-finite-depth",
+    CannotModifyProtectedProperty => "cannot-modify-protected-property",
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{test_deserialize, test_serialize};
+
+    use super::*;
+
+    #[test]
+    fn lock_token_submitted() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---deleting-a-locked-resource
+        let xml = r#"
+<d:error xmlns:d="DAV:">
+  <d:lock-token-submitted>
+    <d:href>/workspace/webdav/</d:href>
+  </d:lock-token-submitted>
+</d:error>
+        "#;
+        let error = Error::from(Condition::LockTokenSubmitted(vec![
+            "/workspace/webdav/".parse()?
+        ]));
+
+        test_deserialize(&error, xml)?;
+        test_serialize(xml, error)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_conditions() -> eyre::Result<()> {
+        let xml = r#"
+<d:error xmlns:d="DAV:">
+  <d:no-conflicting-lock/>
+  <d:propfind-finite-depth/>
+  <d:cannot-modify-protected-property/>
+  <d:preserved-live-properties/>
+  <d:no-external-entities/>
+  <d:lock-token-matches-request-uri/>
+</d:error>
+        "#;
+        let error = Error(vec![
+            Condition::NoConflictingLock(Vec::new()),
+            Condition::PropfindFiniteDepth,
+            Condition::CannotModifyProtectedProperty,
+            Condition::PreservedLiveProperties,
+            Condition::NoExternalEntities,
+            Condition::LockTokenMatchesRequestUri,
+        ]);
+
+        test_deserialize(&error, xml)?;
+        test_serialize(xml, error)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn other() -> eyre::Result<()> {
+        let xml = r#"
+<d:error xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <c:valid-calendar-data/>
+  <d:no-conflicting-lock>
+    <d:href>/container/</d:href>
+  </d:no-conflicting-lock>
+</d:error>
+        "#;
+        let error = Error(vec![
+            Condition::Other(
+                ElementName {
+                    namespace: Some("urn:ietf:params:xml:ns:caldav".into()),
+                    prefix: None,
+                    local_name: "valid-calendar-data".into(),
+                },
+                Value::Empty,
+            ),
+            Condition::NoConflictingLock(vec!["/container/".parse()?]),
+        ]);
+
+        test_deserialize(&error, xml)?;
+
+        Ok(())
+    }
+}
@@ -20,13 +20,17 @@ impl TryFrom<&Value> for Href {
     type Error = ExtractElementError;
 
     fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        match value.to_text()?.parse() {
+        // the text is usually a slice of the parsed document, which is shared
+        // instead of copied
+        match http::Uri::from_maybe_shared(value.to_text()?.as_bytes().clone()) {
             Ok(uri) => Ok(Self(uri)),
             Err(e) => Err(ExtractElementError::other(e)),
         }
     }
 }
 
+crate::utils::owned_try_from!(Href);
+
 impl From<Href> for Value {
     fn from(Href(uri): Href) -> Value {
         Value::Text(uri.to_string().into())
@@ -0,0 +1,46 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    elements::Href, value::ValueMap, Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE,
+    DAV_PREFIX,
+};
+
+/// The `location` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_location).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location(pub Href);
+
+impl Element for Location {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "location";
+}
+
+impl TryFrom<&Value> for Location {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self(map.get().required::<Href>()??))
+    }
+}
+
+crate::utils::owned_try_from!(Location);
+
+impl From<Location> for Value {
+    fn from(Location(href): Location) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<Href>(href.into());
+
+        Value::Map(map)
+    }
+}
+
+impl From<Href> for Location {
+    fn from(href: Href) -> Self {
+        Location(href)
+    }
+}
@@ -0,0 +1,201 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytes::Bytes;
+
+use crate::{
+    elements::{ActiveLock, LockInfo},
+    properties::LockDiscovery,
+    value::ValueMap,
+    Element, ExtractElementError, FromXml, IntoXml, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The request body of a `LOCK` request as defined in
+/// [RFC 4918](http://webdav.org/specs/rfc4918.html#METHOD_LOCK).
+#[derive(Clone, Debug, PartialEq)]
+pub enum LockRequest {
+    /// Create a new lock described by a `lockinfo` element.
+    Create(LockInfo),
+    /// Refresh an existing lock, which is requested using an empty body.
+    Refresh,
+}
+
+impl LockRequest {
+    /// Parse the body of a `LOCK` request.
+    ///
+    /// An empty body (or one that only contains whitespace) is interpreted as
+    /// a lock refresh.
+    pub fn from_body(body: impl Into<Bytes>) -> crate::Result<Self> {
+        let body = body.into();
+
+        if body.iter().all(u8::is_ascii_whitespace) {
+            Ok(Self::Refresh)
+        } else {
+            LockInfo::from_xml(body).map(Self::Create)
+        }
+    }
+    /// Serialize the body of a `LOCK` request.
+    ///
+    /// Returns an empty body for a lock refresh.
+    pub fn into_body(self) -> crate::Result<Bytes> {
+        match self {
+            Self::Create(lockinfo) => lockinfo.into_xml(),
+            Self::Refresh => Ok(Bytes::new()),
+        }
+    }
+}
+
+impl From<LockInfo> for LockRequest {
+    fn from(lockinfo: LockInfo) -> Self {
+        Self::Create(lockinfo)
+    }
+}
+
+/// The response body of a successful `LOCK` request as defined in
+/// [RFC 4918](http://webdav.org/specs/rfc4918.html#METHOD_LOCK).
+///
+/// This is a `prop` element containing only the `lockdiscovery` property.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LockResponse {
+    pub lockdiscovery: LockDiscovery,
+}
+
+impl Element for LockResponse {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "prop";
+}
+
+impl TryFrom<&Value> for LockResponse {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self {
+            lockdiscovery: map.get().required::<LockDiscovery>()??,
+        })
+    }
+}
+
+crate::utils::owned_try_from!(LockResponse);
+
+impl From<LockResponse> for Value {
+    fn from(LockResponse { lockdiscovery }: LockResponse) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<LockDiscovery>(lockdiscovery.into());
+
+        Value::Map(map)
+    }
+}
+
+impl From<ActiveLock> for LockResponse {
+    fn from(activelock: ActiveLock) -> Self {
+        Self {
+            lockdiscovery: LockDiscovery(vec![activelock]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        elements::{Depth, Href, LockRoot, LockScope, LockToken, LockType, Owner, Timeout},
+        utils::{test_deserialize, test_serialize},
+    };
+
+    use super::*;
+
+    fn owner() -> Owner {
+        let mut map = ValueMap::new();
+        map.insert::<Href>(Value::Text("http://example.org/~ejw/contact.html".into()));
+        Owner(Value::Map(map))
+    }
+
+    #[test]
+    fn lockinfo() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---simple-lock-request
+        let xml = r#"
+<d:lockinfo xmlns:d="DAV:">
+  <d:lockscope>
+    <d:exclusive/>
+  </d:lockscope>
+  <d:locktype>
+    <d:write/>
+  </d:locktype>
+  <d:owner>
+    <d:href>http://example.org/~ejw/contact.html</d:href>
+  </d:owner>
+</d:lockinfo>
+        "#;
+        let lockinfo = LockInfo {
+            lockscope: LockScope::Exclusive,
+            locktype: LockType::Write,
+            owner: Some(owner()),
+        };
+
+        test_deserialize(&lockinfo, xml)?;
+        test_serialize(xml, lockinfo.clone())?;
+
+        assert_eq!(LockRequest::from_body(xml)?, LockRequest::Create(lockinfo));
+
+        Ok(())
+    }
+
+    #[test]
+    fn refresh() -> eyre::Result<()> {
+        assert_eq!(LockRequest::from_body("")?, LockRequest::Refresh);
+        assert_eq!(LockRequest::from_body("\r\n")?, LockRequest::Refresh);
+        assert!(LockRequest::Refresh.into_body()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lock_response() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---simple-lock-request
+        let xml = r#"
+<d:prop xmlns:d="DAV:">
+  <d:lockdiscovery>
+    <d:activelock>
+      <d:lockscope>
+        <d:exclusive/>
+      </d:lockscope>
+      <d:locktype>
+        <d:write/>
+      </d:locktype>
+      <d:depth>infinity</d:depth>
+      <d:owner>
+        <d:href>http://example.org/~ejw/contact.html</d:href>
+      </d:owner>
+      <d:timeout>Second-604800</d:timeout>
+      <d:locktoken>
+        <d:href>urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4</d:href>
+      </d:locktoken>
+      <d:lockroot>
+        <d:href>http://example.com/workspace/webdav/proposal.doc</d:href>
+      </d:lockroot>
+    </d:activelock>
+  </d:lockdiscovery>
+</d:prop>
+        "#;
+        let response = LockResponse::from(ActiveLock {
+            lockscope: LockScope::Exclusive,
+            locktype: LockType::Write,
+            depth: Depth::Infinity,
+            owner: Some(owner()),
+            timeout: Some(Timeout::Seconds(604800)),
+            locktoken: Some(LockToken(
+                "urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4".into(),
+            )),
+            lockroot: LockRoot("http://example.com/workspace/webdav/proposal.doc".parse()?),
+        });
+
+        test_deserialize(&response, xml)?;
+        test_serialize(xml, response)?;
+
+        Ok(())
+    }
+}
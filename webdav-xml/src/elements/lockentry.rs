@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    elements::{LockScope, LockType},
+    value::ValueMap,
+    Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `lockentry` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_lockentry).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockEntry {
+    pub lockscope: LockScope,
+    pub locktype: LockType,
+}
+
+impl Element for LockEntry {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "lockentry";
+}
+
+impl TryFrom<&Value> for LockEntry {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self {
+            lockscope: map.get().required::<LockScope>()??,
+            locktype: map.get().required::<LockType>()??,
+        })
+    }
+}
+
+crate::utils::owned_try_from!(LockEntry);
+
+impl From<LockEntry> for Value {
+    fn from(
+        LockEntry {
+            lockscope,
+            locktype,
+        }: LockEntry,
+    ) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<LockScope>(lockscope.into());
+        map.insert::<LockType>(locktype.into());
+
+        Value::Map(map)
+    }
+}
@@ -0,0 +1,59 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    elements::{LockScope, LockType, Owner},
+    value::ValueMap,
+    Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `lockinfo` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_lockinfo).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockInfo {
+    pub lockscope: LockScope,
+    pub locktype: LockType,
+    pub owner: Option<Owner>,
+}
+
+impl Element for LockInfo {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "lockinfo";
+}
+
+impl TryFrom<&Value> for LockInfo {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self {
+            lockscope: map.get().required::<LockScope>()??,
+            locktype: map.get().required::<LockType>()??,
+            owner: map.get().transpose()?,
+        })
+    }
+}
+
+crate::utils::owned_try_from!(LockInfo);
+
+impl From<LockInfo> for Value {
+    fn from(
+        LockInfo {
+            lockscope,
+            locktype,
+            owner,
+        }: LockInfo,
+    ) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<LockScope>(lockscope.into());
+        map.insert::<LockType>(locktype.into());
+        if let Some(owner) = owner {
+            map.insert::<Owner>(owner.into());
+        }
+
+        Value::Map(map)
+    }
+}
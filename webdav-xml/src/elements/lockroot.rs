@@ -0,0 +1,46 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    elements::Href, value::ValueMap, Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE,
+    DAV_PREFIX,
+};
+
+/// The `lockroot` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_lockroot).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockRoot(pub Href);
+
+impl Element for LockRoot {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "lockroot";
+}
+
+impl TryFrom<&Value> for LockRoot {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        Ok(Self(map.get().required::<Href>()??))
+    }
+}
+
+crate::utils::owned_try_from!(LockRoot);
+
+impl From<LockRoot> for Value {
+    fn from(LockRoot(href): LockRoot) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<Href>(href.into());
+
+        Value::Map(map)
+    }
+}
+
+impl From<Href> for LockRoot {
+    fn from(href: Href) -> Self {
+        LockRoot(href)
+    }
+}
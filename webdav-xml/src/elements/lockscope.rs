@@ -0,0 +1,93 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    value::ValueMap, Element, ExtractElementError, ExtractElementErrorKind, Value, DAV_NAMESPACE,
+    DAV_PREFIX,
+};
+
+/// The `lockscope` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_lockscope).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LockScope {
+    Exclusive,
+    Shared,
+}
+
+impl Element for LockScope {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "lockscope";
+}
+
+impl TryFrom<&Value> for LockScope {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        match (map.get::<Exclusive>(), map.get::<Shared>()) {
+            (Some(_), None) => Ok(LockScope::Exclusive),
+            (None, Some(_)) => Ok(LockScope::Shared),
+            (None, None) => Err(ExtractElementError::new(
+                ExtractElementErrorKind::MissingElement(Exclusive::LOCAL_NAME),
+            )),
+            (Some(_), Some(_)) => Err(ExtractElementError::new(
+                ExtractElementErrorKind::ConflictingElements(&["exclusive", "shared"]),
+            )),
+        }
+    }
+}
+
+crate::utils::owned_try_from!(LockScope);
+
+impl From<LockScope> for Value {
+    fn from(lockscope: LockScope) -> Value {
+        let mut map = ValueMap::new();
+
+        match lockscope {
+            LockScope::Exclusive => map.insert::<Exclusive>(Value::Empty),
+            LockScope::Shared => map.insert::<Shared>(Value::Empty),
+        }
+
+        Value::Map(map)
+    }
+}
+
+/// The `exclusive` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_exclusive).
+struct Exclusive;
+
+impl Element for Exclusive {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "exclusive";
+}
+
+impl TryFrom<&Value> for Exclusive {
+    type Error = ExtractElementError;
+
+    fn try_from(_: &Value) -> Result<Self, Self::Error> {
+        Ok(Exclusive)
+    }
+}
+
+crate::utils::owned_try_from!(Exclusive);
+
+/// The `shared` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_shared).
+struct Shared;
+
+impl Element for Shared {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "shared";
+}
+
+impl TryFrom<&Value> for Shared {
+    type Error = ExtractElementError;
+
+    fn try_from(_: &Value) -> Result<Self, Self::Error> {
+        Ok(Shared)
+    }
+}
+
+crate::utils::owned_try_from!(Shared);
@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytestring::ByteString;
+
+use crate::{
+    value::ValueMap, Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `locktoken` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_locktoken).
+///
+/// Lock tokens are usually URNs like `urn:uuid:…`, which can't be represented
+/// by [`Href`](crate::elements::Href), so the token is stored as a string.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockToken(pub ByteString);
+
+impl Element for LockToken {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "locktoken";
+}
+
+impl TryFrom<&Value> for LockToken {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+        let TokenHref(token) = map.get().required::<TokenHref>()??;
+
+        Ok(Self(token))
+    }
+}
+
+crate::utils::owned_try_from!(LockToken);
+
+impl From<LockToken> for Value {
+    fn from(LockToken(token): LockToken) -> Value {
+        let mut map = ValueMap::new();
+
+        map.insert::<TokenHref>(Value::Text(token));
+
+        Value::Map(map)
+    }
+}
+
+impl<S: Into<ByteString>> From<S> for LockToken {
+    fn from(s: S) -> Self {
+        LockToken(s.into())
+    }
+}
+
+/// The `href` XML element containing a lock token.
+struct TokenHref(ByteString);
+
+impl Element for TokenHref {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "href";
+}
+
+impl TryFrom<&Value> for TokenHref {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(Self(value.to_text()?.clone()))
+    }
+}
+
+crate::utils::owned_try_from!(TokenHref);
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytestring::ByteString;
+
+use crate::{
+    element::ElementName, value::ValueMap, Element, ExtractElementError, ExtractElementErrorKind,
+    Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `locktype` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_locktype).
+#[derive(Clone, Debug, PartialEq)]
+pub enum LockType {
+    Write,
+    /// A lock type defined by an extension to RFC 4918.
+    Other(ElementName<ByteString>),
+}
+
+impl Element for LockType {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "locktype";
+}
+
+impl TryFrom<&Value> for LockType {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        if map.get::<Write>().is_some() {
+            return Ok(LockType::Write);
+        }
+
+        match map.map.keys().next() {
+            Some(name) => Ok(LockType::Other(name.clone())),
+            None => Err(ExtractElementError::new(
+                ExtractElementErrorKind::MissingElement(Write::LOCAL_NAME),
+            )),
+        }
+    }
+}
+
+crate::utils::owned_try_from!(LockType);
+
+impl From<LockType> for Value {
+    fn from(locktype: LockType) -> Value {
+        let mut map = ValueMap::new();
+
+        match locktype {
+            LockType::Write => map.insert::<Write>(Value::Empty),
+            LockType::Other(name) => map.insert_raw(name, Value::Empty),
+        }
+
+        Value::Map(map)
+    }
+}
+
+/// The `write` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_write).
+struct Write;
+
+impl Element for Write {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "write";
+}
+
+impl TryFrom<&Value> for Write {
+    type Error = ExtractElementError;
+
+    fn try_from(_: &Value) -> Result<Self, Self::Error> {
+        Ok(Write)
+    }
+}
+
+crate::utils::owned_try_from!(Write);
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        utils::{test_deserialize, test_serialize},
+        FromXml, IntoXml,
+    };
+
+    use super::*;
+
+    #[test]
+    fn write() -> eyre::Result<()> {
+        let xml = r#"
+<d:locktype xmlns:d="DAV:">
+  <d:write/>
+</d:locktype>
+        "#;
+        let locktype = LockType::Write;
+
+        test_deserialize(&locktype, xml)?;
+        test_serialize(xml, locktype)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn other() -> eyre::Result<()> {
+        let xml = r#"
+<d:locktype xmlns:d="DAV:" xmlns:x="http://example.com/ns">
+  <x:checkout/>
+</d:locktype>
+        "#;
+        let locktype = LockType::Other(ElementName {
+            namespace: Some("http://example.com/ns".into()),
+            prefix: Some("x".into()),
+            local_name: "checkout".into(),
+        });
+
+        test_deserialize(&locktype, xml)?;
+        test_serialize(xml, locktype.clone())?;
+
+        // a namespace without a prefix is declared with a generated one
+        let locktype = LockType::Other(ElementName {
+            namespace: Some("http://example.com/ns".into()),
+            prefix: None,
+            local_name: "checkout".into(),
+        });
+        let xml = locktype.clone().into_xml()?;
+        assert_eq!(LockType::from_xml(xml)?, locktype);
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing() {
+        let xml = r#"<d:locktype xmlns:d="DAV:"></d:locktype>"#;
+
+        assert!(LockType::from_xml(xml).is_err());
+    }
+}
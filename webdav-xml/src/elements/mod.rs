@@ -5,22 +5,52 @@
 //! XML element definitions based on
 //! [RFC 4918](http://webdav.org/specs/rfc4918.html#xml.element.definitions).
 
+mod activelock;
+mod builders;
+mod depth;
+mod error;
 mod href;
+mod location;
+mod lock;
+mod lockentry;
+mod lockinfo;
+mod lockroot;
+mod lockscope;
+mod locktoken;
+mod locktype;
 mod multistatus;
+mod owner;
 mod prop;
+mod propertyupdate;
 mod propfind;
 mod propstat;
 mod response;
 mod responsedescription;
 mod status;
+mod timeout;
 
 pub use self::{
+    activelock::ActiveLock,
+    builders::{MemberStatusBuilder, ProppatchResponseBuilder},
+    depth::Depth,
+    error::{Condition, Error},
     href::Href,
+    location::Location,
+    lock::{LockRequest, LockResponse},
+    lockentry::LockEntry,
+    lockinfo::LockInfo,
+    lockroot::LockRoot,
+    lockscope::LockScope,
+    locktoken::LockToken,
+    locktype::LockType,
     multistatus::Multistatus,
+    owner::Owner,
     prop::Properties,
-    propfind::{Include, Propfind},
+    propertyupdate::{Instruction, PropertyUpdate},
+    propfind::{Allprop, Include, Propfind, Propname},
     propstat::Propstat,
     response::Response,
     responsedescription::ResponseDescription,
     status::Status,
+    timeout::Timeout,
 };
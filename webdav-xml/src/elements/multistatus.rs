@@ -36,6 +36,19 @@ impl TryFrom<&Value> for Multistatus {
     }
 }
 
+impl TryFrom<Value> for Multistatus {
+    type Error = ExtractElementError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut map = value.into_map()?;
+
+        Ok(Multistatus {
+            response: map.take_all().collect::<Result<_, _>>()?,
+            responsedescription: map.get().transpose()?,
+        })
+    }
+}
+
 impl From<Multistatus> for Value {
     fn from(
         Multistatus {
@@ -61,7 +74,7 @@ impl From<Multistatus> for Value {
 
 #[cfg(test)]
 mod tests {
-    use crate::FromXml as _;
+    use crate::{FromXml as _, OptionExt};
 
     use super::*;
 
@@ -114,6 +127,14 @@ mod tests {
               && propstat[1].responsedescription.is_some()
         ));
 
+        // extracting from an owned value moves the properties instead of
+        // cloning them, but gives the same result
+        let owned = Value::from_xml(xml)?
+            .into_map()?
+            .take::<Multistatus>()
+            .required::<Multistatus>()??;
+        assert_eq!(owned, multistatus);
+
         Ok(())
     }
 
@@ -154,4 +175,75 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn location() -> eyre::Result<()> {
+        use http::StatusCode;
+
+        use crate::{
+            elements::{Href, Location, Status},
+            utils::{test_deserialize, test_serialize},
+        };
+
+        let xml = r#"
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/container/old</d:href>
+    <d:status>HTTP/1.1 301 Moved Permanently</d:status>
+    <d:location>
+      <d:href>http://www.example.com/container/new</d:href>
+    </d:location>
+  </d:response>
+</d:multistatus>
+        "#;
+        let multistatus = Multistatus {
+            response: vec![Response::Status {
+                href: nonempty::nonempty![Href("/container/old".parse()?)],
+                status: Status(StatusCode::MOVED_PERMANENTLY),
+                error: None,
+                responsedescription: None,
+                location: Some(Location(Href(
+                    "http://www.example.com/container/new".parse()?,
+                ))),
+            }],
+            responsedescription: None,
+        };
+
+        test_deserialize(&multistatus, xml)?;
+        test_serialize(xml, multistatus)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() -> eyre::Result<()> {
+        use http::StatusCode;
+
+        use crate::elements::{Condition, Error, Status};
+
+        // http://webdav.org/specs/rfc4918.html#n-example---move-and-the-overwrite-header
+        let xml = r#"
+        <?xml version="1.0" encoding="utf-8" ?>
+        <d:multistatus xmlns:d='DAV:'>
+          <d:response>
+            <d:href>http://www.example.com/othercontainer/C2/</d:href>
+            <d:status>HTTP/1.1 423 Locked</d:status>
+            <d:error><d:lock-token-submitted/></d:error>
+          </d:response>
+        </d:multistatus>
+        "#;
+        let multistatus = Multistatus::from_xml(xml)?;
+
+        assert!(matches!(
+            &multistatus.response[0],
+            Response::Status {
+                status,
+                error: Some(Error(conditions)),
+                ..
+            } if status == &Status(StatusCode::LOCKED)
+              && conditions == &[Condition::LockTokenSubmitted(Vec::new())]
+        ));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,38 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Element, ExtractElementError, Value, DAV_NAMESPACE, DAV_PREFIX};
+
+/// The `owner` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_owner).
+///
+/// The content of this element is provided by the client and may contain
+/// arbitrary XML, so it is stored as a raw [`Value`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Owner(pub Value);
+
+impl Element for Owner {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "owner";
+}
+
+impl TryFrom<&Value> for Owner {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(Self(value.clone()))
+    }
+}
+
+impl From<Owner> for Value {
+    fn from(Owner(value): Owner) -> Value {
+        value
+    }
+}
+
+impl From<Value> for Owner {
+    fn from(value: Value) -> Self {
+        Owner(value)
+    }
+}
@@ -39,6 +39,17 @@ impl Properties {
         self.0.insert::<E>(Value::Empty);
         self
     }
+    /// Add a property with an arbitrary value, e.g. a dead property.
+    pub fn with_raw(mut self, name: ElementName<ByteString>, value: Value) -> Self {
+        self.0.insert_raw(name, value);
+        self
+    }
+    pub(crate) fn insert_name(&mut self, name: ElementName<ByteString>) {
+        self.0.insert_raw(name, Value::Empty);
+    }
+    pub(crate) fn insert_raw(&mut self, name: ElementName<ByteString>, value: Value) {
+        self.0.insert_raw(name, value);
+    }
 }
 
 impl Properties {
@@ -56,9 +67,28 @@ impl Properties {
     {
         self.0.get_optional()
     }
+    /// Read the raw value of a property, e.g. a dead property that doesn't
+    /// have a corresponding [`Element`] implementation.
+    pub fn get_raw(&self, name: &ElementName<ByteString>) -> Option<&Value> {
+        self.0.map.get(name)
+    }
     /// List the names of the properties in this `prop` element.
     pub fn names(&self) -> impl Iterator<Item = &ElementName<ByteString>> {
-        self.0 .0.keys()
+        self.0.map.keys()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Properties {
+    /// Deserialize the properties into a type implementing
+    /// [`serde::Deserialize`], e.g. a struct with a field for each property.
+    ///
+    /// See [`crate::serde`] for how property names are matched.
+    pub fn deserialize<'de, T>(&'de self) -> Result<T, ExtractElementError>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        T::deserialize(&self.0)
     }
 }
 
@@ -141,6 +171,14 @@ impl TryFrom<&Value> for Properties {
     }
 }
 
+impl TryFrom<Value> for Properties {
+    type Error = ExtractElementError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_map().map(Self)
+    }
+}
+
 impl From<Properties> for Value {
     fn from(Properties(map): Properties) -> Value {
         Value::Map(map)
@@ -183,3 +221,82 @@ fn test_deserialize() -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+#[test]
+fn test_dead_property() -> eyre::Result<()> {
+    use pretty_assertions::assert_eq;
+
+    use crate::{value::Node, FromXml, IntoXml};
+
+    let xml = r#"
+    <d:prop xmlns:d="DAV:" xmlns:x="http://example.com/ns">
+        <x:note>Hello <x:b>world</x:b>!</x:note>
+    </d:prop>
+    "#;
+
+    let prop = Properties::from_xml(xml)?;
+
+    let name = ElementName {
+        namespace: Some("http://example.com/ns".into()),
+        prefix: Some("x".into()),
+        local_name: "note".into(),
+    };
+    let b = ElementName {
+        local_name: "b".into(),
+        ..name.clone()
+    };
+    let value = Value::Mixed(vec![
+        Node::Text("Hello ".into()),
+        Node::Element(b, Value::Text("world".into())),
+        Node::Text("!".into()),
+    ]);
+    assert_eq!(prop.get_raw(&name), Some(&value));
+
+    // the text nodes would contain indentation if it was added to mixed content
+    assert_eq!(Properties::from_xml(prop.clone().into_xml()?)?, prop);
+
+    assert_eq!(Properties::new().with_raw(name, value), prop);
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn test_dead_property_whitespace() -> eyre::Result<()> {
+    use pretty_assertions::assert_eq;
+
+    use crate::{value::Node, FromXml, IntoXml};
+
+    let xml = r#"
+    <d:prop xmlns:d="DAV:" xmlns:x="http://example.com/ns">
+        <x:note><x:b>a</x:b> <x:i>b</x:i></x:note>
+    </d:prop>
+    "#;
+
+    let prop = Properties::from_xml(xml)?;
+
+    let name = ElementName {
+        namespace: Some("http://example.com/ns".into()),
+        prefix: Some("x".into()),
+        local_name: "note".into(),
+    };
+    let b = ElementName {
+        local_name: "b".into(),
+        ..name.clone()
+    };
+    let i = ElementName {
+        local_name: "i".into(),
+        ..name.clone()
+    };
+    let value = Value::Mixed(vec![
+        Node::Element(b, Value::Text("a".into())),
+        Node::Text(" ".into()),
+        Node::Element(i, Value::Text("b".into())),
+    ]);
+    assert_eq!(prop.get_raw(&name), Some(&value));
+
+    assert_eq!(Properties::from_xml(prop.clone().into_xml()?)?, prop);
+
+    Ok(())
+}
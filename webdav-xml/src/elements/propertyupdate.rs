@@ -0,0 +1,241 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use nonempty::NonEmpty;
+
+use crate::{
+    elements::Properties, utils::NonEmptyExt, value::ValueMap, Element, ExtractElementError,
+    OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
+};
+
+/// The `propertyupdate` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_propertyupdate).
+///
+/// The instructions are kept in document order, because servers must process
+/// them in this order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyUpdate(pub NonEmpty<Instruction>);
+
+/// A `set` or `remove` instruction inside a [`PropertyUpdate`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+    /// The `set` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_set).
+    Set(Properties),
+    /// The `remove` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_remove).
+    Remove(Properties),
+}
+
+impl Element for PropertyUpdate {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "propertyupdate";
+}
+
+impl TryFrom<&Value> for PropertyUpdate {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let map = value.to_map()?;
+
+        let (mut sets, mut removes) = (0, 0);
+        let instructions = map.iter().filter_map(|(name, value)| {
+            let (instruction, index): (fn(_) -> _, _) = if name.is::<Set>() {
+                sets += 1;
+                (Instruction::Set, sets - 1)
+            } else if name.is::<Remove>() {
+                removes += 1;
+                (Instruction::Remove, removes - 1)
+            } else {
+                return None;
+            };
+            Some(
+                prop(value)
+                    .map(instruction)
+                    .map_err(|e| e.within(name.clone(), Some(index))),
+            )
+        });
+
+        Ok(Self(
+            NonEmpty::try_collect(instructions)?.required::<Set>()?,
+        ))
+    }
+}
+
+crate::utils::owned_try_from!(PropertyUpdate);
+
+impl From<PropertyUpdate> for Value {
+    fn from(PropertyUpdate(instructions): PropertyUpdate) -> Value {
+        let mut map = ValueMap::new();
+
+        for instruction in instructions {
+            match instruction {
+                Instruction::Set(prop) => map.insert::<Set>(wrap_prop(prop)),
+                Instruction::Remove(prop) => map.insert::<Remove>(wrap_prop(prop)),
+            }
+        }
+
+        Value::Map(map)
+    }
+}
+
+fn prop(value: &Value) -> Result<Properties, ExtractElementError> {
+    value.to_map()?.get().required::<Properties>()?
+}
+
+fn wrap_prop(prop: Properties) -> Value {
+    let mut map = ValueMap::new();
+    map.insert::<Properties>(prop.into());
+    Value::Map(map)
+}
+
+struct Set;
+
+impl Element for Set {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "set";
+}
+
+struct Remove;
+
+impl Element for Remove {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "remove";
+}
+
+#[cfg(test)]
+mod tests {
+    use nonempty::nonempty;
+
+    use crate::{
+        properties::{ContentLanguage, DisplayName},
+        utils::{test_deserialize, test_serialize},
+        FromXml,
+    };
+
+    use super::*;
+
+    struct Authors;
+
+    impl Element for Authors {
+        const NAMESPACE: &'static str = "http://ns.example.com/standards/z39.50/";
+        const PREFIX: &'static str = "Z";
+        const LOCAL_NAME: &'static str = "Authors";
+    }
+
+    struct CopyrightOwner;
+
+    impl Element for CopyrightOwner {
+        const NAMESPACE: &'static str = "http://ns.example.com/standards/z39.50/";
+        const PREFIX: &'static str = "Z";
+        const LOCAL_NAME: &'static str = "Copyright-Owner";
+    }
+
+    #[test]
+    fn rfc_example() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---proppatch
+        let xml = r#"
+        <?xml version="1.0" encoding="utf-8" ?>
+        <D:propertyupdate xmlns:D="DAV:"
+                xmlns:Z="http://ns.example.com/standards/z39.50/">
+          <D:set>
+            <D:prop>
+              <Z:Authors>
+                <Z:Author>Jim Whitehead</Z:Author>
+                <Z:Author>Roy Fielding</Z:Author>
+              </Z:Authors>
+            </D:prop>
+          </D:set>
+          <D:remove>
+            <D:prop><Z:Copyright-Owner/></D:prop>
+          </D:remove>
+        </D:propertyupdate>
+        "#;
+
+        let PropertyUpdate(instructions) = PropertyUpdate::from_xml(xml)?;
+
+        assert_eq!(instructions.len(), 2);
+        assert!(matches!(
+            &instructions[0],
+            Instruction::Set(prop) if prop.names().any(|name| name.is::<Authors>())
+        ));
+        assert_eq!(
+            instructions[1],
+            Instruction::Remove(Properties::new().with_name::<CopyrightOwner>())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn interleaved() -> eyre::Result<()> {
+        let xml = r#"
+<d:propertyupdate xmlns:d="DAV:">
+  <d:set>
+    <d:prop>
+      <d:displayname>foo</d:displayname>
+    </d:prop>
+  </d:set>
+  <d:remove>
+    <d:prop>
+      <d:displayname/>
+    </d:prop>
+  </d:remove>
+  <d:set>
+    <d:prop>
+      <d:displayname>bar</d:displayname>
+      <d:getcontentlanguage>en</d:getcontentlanguage>
+    </d:prop>
+  </d:set>
+</d:propertyupdate>
+        "#;
+        let propertyupdate = PropertyUpdate(nonempty![
+            Instruction::Set(Properties::new().with(DisplayName("foo".into()))),
+            Instruction::Remove(Properties::new().with_name::<DisplayName>()),
+            Instruction::Set(
+                Properties::new()
+                    .with(DisplayName("bar".into()))
+                    .with(ContentLanguage("en".into()))
+            ),
+        ]);
+
+        test_deserialize(&propertyupdate, xml)?;
+        test_serialize(xml, propertyupdate)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn attributes() -> eyre::Result<()> {
+        let xml = r#"
+<d:propertyupdate xmlns:d="DAV:">
+  <d:set>
+    <d:prop>
+      <d:displayname xml:lang="en">foo</d:displayname>
+    </d:prop>
+  </d:set>
+</d:propertyupdate>
+        "#;
+        let propertyupdate = PropertyUpdate::from_xml(xml)?;
+
+        let Instruction::Set(prop) = &propertyupdate.0.head else {
+            panic!("expected a set instruction");
+        };
+        assert_eq!(
+            prop.displayname().unwrap().unwrap()?,
+            DisplayName("foo".into())
+        );
+
+        test_serialize(xml, propertyupdate)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty() {
+        let xml = r#"<d:propertyupdate xmlns:d="DAV:"></d:propertyupdate>"#;
+
+        assert!(PropertyUpdate::from_xml(xml).is_err());
+    }
+}
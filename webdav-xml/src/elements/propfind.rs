@@ -5,8 +5,10 @@
 use bytestring::ByteString;
 
 use crate::{
-    elements::Properties, Element, ExtractElementError, ExtractElementErrorKind, Value,
-    DAV_NAMESPACE, DAV_PREFIX,
+    element::{ElementExt, ElementName},
+    elements::Properties,
+    value::ValueMap,
+    Element, ExtractElementError, ExtractElementErrorKind, Value, DAV_NAMESPACE, DAV_PREFIX,
 };
 
 /// The `propfind` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_propfind).
@@ -27,7 +29,7 @@ impl TryFrom<&Value> for Propfind {
     type Error = ExtractElementError;
 
     fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        let map = value.to_map()?;
+        let map = crate::__private::to_map(value)?;
 
         match (
             map.get::<Propname>(),
@@ -39,13 +41,37 @@ impl TryFrom<&Value> for Propfind {
                 include: map.get().transpose()?,
             }),
             (None, None, Some(prop)) => Ok(Propfind::Prop(prop?)),
+            (None, None, None) => Err(ExtractElementError::new(
+                ExtractElementErrorKind::MissingElement(Propname::LOCAL_NAME),
+            )),
             _ => Err(ExtractElementError::new(
-                ExtractElementErrorKind::ConflictingElements(&["propname", "allprop", "include"]),
+                ExtractElementErrorKind::ConflictingElements(&["propname", "allprop", "prop"]),
             )),
         }
     }
 }
 
+crate::utils::owned_try_from!(Propfind);
+
+impl From<Propfind> for Value {
+    fn from(propfind: Propfind) -> Value {
+        let mut map = ValueMap::new();
+
+        match propfind {
+            Propfind::Propname => map.insert::<Propname>(Propname.into()),
+            Propfind::Allprop { include } => {
+                map.insert::<Allprop>(Allprop.into());
+                if let Some(include) = include {
+                    map.insert::<Include>(include.into());
+                }
+            }
+            Propfind::Prop(prop) => map.insert::<Properties>(prop.into()),
+        }
+
+        Value::Map(map)
+    }
+}
+
 /// The `propname` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_propname).
 #[derive(Clone, Debug, PartialEq)]
 pub struct Propname;
@@ -64,6 +90,14 @@ impl TryFrom<&Value> for Propname {
     }
 }
 
+crate::utils::owned_try_from!(Propname);
+
+impl From<Propname> for Value {
+    fn from(_: Propname) -> Value {
+        Value::Empty
+    }
+}
+
 /// The `allprop` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_allprop).
 #[derive(Clone, Debug, PartialEq)]
 pub struct Allprop;
@@ -82,9 +116,33 @@ impl TryFrom<&Value> for Allprop {
     }
 }
 
+crate::utils::owned_try_from!(Allprop);
+
+impl From<Allprop> for Value {
+    fn from(_: Allprop) -> Value {
+        Value::Empty
+    }
+}
+
 /// The `include` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_include).
-#[derive(Clone, Debug, PartialEq)]
-pub struct Include(Vec<ByteString>);
+///
+/// Contains the names of additional properties that should be returned
+/// together with `allprop`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Include(pub Vec<ElementName<ByteString>>);
+
+impl Include {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_name<E>(mut self) -> Self
+    where
+        E: Element,
+    {
+        self.0.push(E::element_name());
+        self
+    }
+}
 
 impl Element for Include {
     const NAMESPACE: &'static str = DAV_NAMESPACE;
@@ -95,7 +153,210 @@ impl Element for Include {
 impl TryFrom<&Value> for Include {
     type Error = ExtractElementError;
 
-    fn try_from(_: &Value) -> Result<Self, Self::Error> {
-        todo!()
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.without_attributes() {
+            Value::Empty => Ok(Self::default()),
+            value => Ok(Self(
+                value
+                    .to_map()?
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+            )),
+        }
+    }
+}
+
+crate::utils::owned_try_from!(Include);
+
+impl From<Include> for Value {
+    fn from(Include(names): Include) -> Value {
+        if names.is_empty() {
+            return Value::Empty;
+        }
+
+        let mut map = ValueMap::new();
+
+        for name in names {
+            map.insert_raw(name, Value::Empty);
+        }
+
+        Value::Map(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        properties::{DisplayName, ResourceType},
+        utils::{test_deserialize, test_serialize},
+        FromXml,
+    };
+
+    use super::*;
+
+    struct SupportedLivePropertySet;
+
+    impl Element for SupportedLivePropertySet {
+        const NAMESPACE: &'static str = DAV_NAMESPACE;
+        const PREFIX: &'static str = DAV_PREFIX;
+        const LOCAL_NAME: &'static str = "supported-live-property-set";
+    }
+
+    struct SupportedReportSet;
+
+    impl Element for SupportedReportSet {
+        const NAMESPACE: &'static str = DAV_NAMESPACE;
+        const PREFIX: &'static str = DAV_PREFIX;
+        const LOCAL_NAME: &'static str = "supported-report-set";
+    }
+
+    struct BigBox;
+
+    impl Element for BigBox {
+        const NAMESPACE: &'static str = "http://ns.example.com/boxschema/";
+        const PREFIX: &'static str = "R";
+        const LOCAL_NAME: &'static str = "bigbox";
+    }
+
+    struct Author;
+
+    impl Element for Author {
+        const NAMESPACE: &'static str = "http://ns.example.com/boxschema/";
+        const PREFIX: &'static str = "R";
+        const LOCAL_NAME: &'static str = "author";
+    }
+
+    #[test]
+    fn propname() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---using-propname-to-retrieve-all-property-names
+        let xml = r#"
+        <?xml version="1.0" encoding="utf-8" ?>
+        <propfind xmlns="DAV:">
+          <propname/>
+        </propfind>
+        "#;
+
+        test_deserialize(&Propfind::Propname, xml)?;
+        test_serialize(
+            r#"
+<d:propfind xmlns:d="DAV:">
+  <d:propname/>
+</d:propfind>
+            "#,
+            Propfind::Propname,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn allprop() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---using-so-called-allprop
+        let xml = r#"
+<d:propfind xmlns:d="DAV:">
+  <d:allprop/>
+</d:propfind>
+        "#;
+        let propfind = Propfind::Allprop { include: None };
+
+        test_deserialize(&propfind, xml)?;
+        test_serialize(xml, propfind)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn allprop_include() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---using-allprop-with-include
+        let xml = r#"
+<d:propfind xmlns:d="DAV:">
+  <d:allprop/>
+  <d:include>
+    <d:supported-live-property-set/>
+    <d:supported-report-set/>
+  </d:include>
+</d:propfind>
+        "#;
+        let propfind = Propfind::Allprop {
+            include: Some(
+                Include::new()
+                    .with_name::<SupportedLivePropertySet>()
+                    .with_name::<SupportedReportSet>(),
+            ),
+        };
+
+        test_deserialize(&propfind, xml)?;
+        test_serialize(xml, propfind)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn prop() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---retrieving-named-properties
+        let xml = r#"
+        <?xml version="1.0" encoding="utf-8" ?>
+        <D:propfind xmlns:D="DAV:">
+          <D:prop xmlns:R="http://ns.example.com/boxschema/">
+            <R:bigbox/>
+            <R:author/>
+          </D:prop>
+        </D:propfind>
+        "#;
+        let propfind = Propfind::Prop(
+            Properties::new()
+                .with_name::<BigBox>()
+                .with_name::<Author>(),
+        );
+
+        test_deserialize(&propfind, xml)?;
+
+        let xml = r#"
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:displayname/>
+    <d:resourcetype/>
+  </d:prop>
+</d:propfind>
+        "#;
+        let propfind = Propfind::Prop(
+            Properties::new()
+                .with_name::<DisplayName>()
+                .with_name::<ResourceType>(),
+        );
+
+        test_deserialize(&propfind, xml)?;
+        test_serialize(xml, propfind)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn conflicting() {
+        let xml = r#"
+        <D:propfind xmlns:D="DAV:">
+          <D:propname/>
+          <D:allprop/>
+        </D:propfind>
+        "#;
+
+        assert!(Propfind::from_xml(xml).is_err());
+    }
+
+    #[test]
+    fn empty() {
+        for xml in [
+            r#"<D:propfind xmlns:D="DAV:"/>"#,
+            r#"<D:propfind xmlns:D="DAV:"></D:propfind>"#,
+        ] {
+            let Err(crate::Error::ExtractElement(e)) = Propfind::from_xml(xml) else {
+                panic!("expected an extraction error");
+            };
+            assert!(matches!(
+                e.kind,
+                ExtractElementErrorKind::MissingElement("propname")
+            ));
+        }
     }
 }
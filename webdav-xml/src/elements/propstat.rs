@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{
-    elements::{Properties, ResponseDescription, Status},
+    elements::{Error, Properties, ResponseDescription, Status},
     value::ValueMap,
     Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
 };
@@ -13,7 +13,7 @@ use crate::{
 pub struct Propstat {
     pub prop: Properties,
     pub status: Status,
-    // pub error: Option<Error>,
+    pub error: Option<Error>,
     pub responsedescription: Option<ResponseDescription>,
 }
 
@@ -31,6 +31,21 @@ impl TryFrom<&Value> for Propstat {
         Ok(Self {
             prop: map.get().required::<Properties>()??,
             status: map.get().required::<Status>()??,
+            error: map.get().transpose()?,
+            responsedescription: map.get().transpose()?,
+        })
+    }
+}
+
+impl TryFrom<Value> for Propstat {
+    type Error = ExtractElementError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut map = value.into_map()?;
+        Ok(Self {
+            prop: map.take().required::<Properties>()??,
+            status: map.get().required::<Status>()??,
+            error: map.get().transpose()?,
             responsedescription: map.get().transpose()?,
         })
     }
@@ -41,6 +56,7 @@ impl From<Propstat> for Value {
         Propstat {
             prop,
             status,
+            error,
             responsedescription,
         }: Propstat,
     ) -> Value {
@@ -48,6 +64,9 @@ impl From<Propstat> for Value {
 
         map.insert::<Properties>(prop.into());
         map.insert::<Status>(status.into());
+        if let Some(error) = error {
+            map.insert::<Error>(error.into())
+        }
         if let Some(responsedescription) = responsedescription {
             map.insert::<ResponseDescription>(responsedescription.into())
         }
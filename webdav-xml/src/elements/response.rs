@@ -5,7 +5,7 @@
 use nonempty::NonEmpty;
 
 use crate::{
-    elements::{Href, Propstat, ResponseDescription, Status},
+    elements::{Error, Href, Location, Propstat, ResponseDescription, Status},
     utils::NonEmptyExt,
     value::ValueMap,
     Element, ExtractElementError, OptionExt, Value, DAV_NAMESPACE, DAV_PREFIX,
@@ -17,16 +17,16 @@ pub enum Response {
     Propstat {
         href: Href,
         propstat: NonEmpty<Propstat>,
-        // error: Option<Error>,
+        error: Option<Error>,
         responsedescription: Option<ResponseDescription>,
-        // location: Option<Location>,
+        location: Option<Location>,
     },
     Status {
         href: NonEmpty<Href>,
         status: Status,
-        // error: Option<Error>,
+        error: Option<Error>,
         responsedescription: Option<ResponseDescription>,
-        // location: Option<Location>,
+        location: Option<Location>,
     },
 }
 
@@ -46,12 +46,41 @@ impl TryFrom<&Value> for Response {
             Some(propstat) => Ok(Self::Propstat {
                 href: map.get().required::<Href>()??,
                 propstat,
+                error: map.get().transpose()?,
                 responsedescription: map.get().transpose()?,
+                location: map.get().transpose()?,
             }),
             None => Ok(Self::Status {
                 href: NonEmpty::try_collect(map.iter_all())?.required::<Href>()?,
                 status: map.get().required::<Status>()??,
+                error: map.get().transpose()?,
                 responsedescription: map.get().transpose()?,
+                location: map.get().transpose()?,
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for Response {
+    type Error = ExtractElementError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut map = value.into_map()?;
+
+        match NonEmpty::try_collect(map.take_all::<Propstat>())? {
+            Some(propstat) => Ok(Self::Propstat {
+                href: map.get().required::<Href>()??,
+                propstat,
+                error: map.get().transpose()?,
+                responsedescription: map.get().transpose()?,
+                location: map.get().transpose()?,
+            }),
+            None => Ok(Self::Status {
+                href: NonEmpty::try_collect(map.iter_all())?.required::<Href>()?,
+                status: map.get().required::<Status>()??,
+                error: map.get().transpose()?,
+                responsedescription: map.get().transpose()?,
+                location: map.get().transpose()?,
             }),
         }
     }
@@ -65,7 +94,9 @@ impl From<Response> for Value {
             Response::Propstat {
                 href,
                 propstat,
+                error,
                 responsedescription,
+                location,
             } => {
                 map.insert::<Href>(href.into());
                 map.insert::<Propstat>(Value::List(Box::new(
@@ -73,14 +104,22 @@ impl From<Response> for Value {
                         "iterator is created from a `NonEmpty` and is guaranteed to be nonempty",
                     ),
                 )));
+                if let Some(error) = error {
+                    map.insert::<Error>(error.into())
+                }
                 if let Some(responsedescription) = responsedescription {
                     map.insert::<ResponseDescription>(responsedescription.into())
                 }
+                if let Some(location) = location {
+                    map.insert::<Location>(location.into())
+                }
             }
             Response::Status {
                 href,
                 status,
+                error,
                 responsedescription,
+                location,
             } => {
                 map.insert::<Href>(Value::List(Box::new(
                     NonEmpty::collect(href.into_iter().map(Value::from)).expect(
@@ -88,9 +127,15 @@ impl From<Response> for Value {
                     ),
                 )));
                 map.insert::<Status>(status.into());
+                if let Some(error) = error {
+                    map.insert::<Error>(error.into())
+                }
                 if let Some(responsedescription) = responsedescription {
                     map.insert::<ResponseDescription>(responsedescription.into())
                 }
+                if let Some(location) = location {
+                    map.insert::<Location>(location.into())
+                }
             }
         }
 
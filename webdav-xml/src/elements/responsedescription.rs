@@ -24,6 +24,8 @@ impl TryFrom<&Value> for ResponseDescription {
     }
 }
 
+crate::utils::owned_try_from!(ResponseDescription);
+
 impl From<ResponseDescription> for Value {
     fn from(ResponseDescription(s): ResponseDescription) -> Value {
         Value::Text(s)
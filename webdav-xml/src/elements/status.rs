@@ -51,6 +51,8 @@ impl TryFrom<&Value> for Status {
         }
     }
 }
+
+crate::utils::owned_try_from!(Status);
 impl From<Status> for Value {
     fn from(status: Status) -> Value {
         status.to_string().into()
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Element, ExtractElementError, Value, DAV_NAMESPACE, DAV_PREFIX};
+
+/// The `timeout` XML element as defined in [RFC 4918](http://webdav.org/specs/rfc4918.html#ELEMENT_timeout).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timeout {
+    Seconds(u32),
+    Infinite,
+}
+
+impl Element for Timeout {
+    const NAMESPACE: &'static str = DAV_NAMESPACE;
+    const PREFIX: &'static str = DAV_PREFIX;
+    const LOCAL_NAME: &'static str = "timeout";
+}
+
+impl FromStr for Timeout {
+    type Err = InvalidTimeout;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("Infinite") {
+            return Ok(Self::Infinite);
+        }
+
+        match s.get(..7) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("Second-") => s[7..]
+                .parse()
+                .map(Self::Seconds)
+                .map_err(|_| InvalidTimeout(s.into())),
+            _ => Err(InvalidTimeout(s.into())),
+        }
+    }
+}
+
+impl Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Seconds(seconds) => write!(f, "Second-{seconds}"),
+            Self::Infinite => f.write_str("Infinite"),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Timeout {
+    type Error = ExtractElementError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.to_text()?.parse() {
+            Ok(timeout) => Ok(timeout),
+            Err(e) => Err(ExtractElementError::other(e)),
+        }
+    }
+}
+
+crate::utils::owned_try_from!(Timeout);
+
+impl From<Timeout> for Value {
+    fn from(timeout: Timeout) -> Value {
+        timeout.to_string().into()
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidTimeout(String);
+
+impl std::fmt::Display for InvalidTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid timeout: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidTimeout {}
+
+#[cfg(test)]
+#[test]
+fn test() -> eyre::Result<()> {
+    assert_eq!("Second-604800", Timeout::Seconds(604800).to_string());
+    assert_eq!("Infinite", Timeout::Infinite.to_string());
+    assert_eq!(
+        Timeout::from_str("Second-604800")?,
+        Timeout::Seconds(604800)
+    );
+    assert_eq!(Timeout::from_str("infinite")?, Timeout::Infinite);
+    assert!(Timeout::from_str("Second-").is_err());
+
+    Ok(())
+}
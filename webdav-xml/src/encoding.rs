@@ -0,0 +1,153 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytes::Bytes;
+use encoding_rs::{Encoding, REPLACEMENT, UTF_16BE, UTF_16LE, UTF_8};
+use quick_xml::events::BytesDecl;
+
+use crate::{XmlError, XmlErrorKind};
+
+/// Transcodes a document to UTF-8 if it uses a different encoding.
+///
+/// The encoding is detected from the byte order mark or the XML declaration
+/// as described in [Appendix F](https://www.w3.org/TR/xml/#sec-guessing) of
+/// the XML specification. UTF-8 documents are returned without copying.
+pub(crate) fn to_utf8(xml: Bytes) -> Result<Bytes, XmlError> {
+    let (encoding, bom_len) = match Encoding::for_bom(&xml) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => (detect(&xml)?, 0),
+    };
+    let xml = xml.slice(bom_len..);
+
+    if encoding == UTF_8 {
+        return Ok(xml);
+    }
+    match encoding.decode_without_bom_handling_and_without_replacement(&xml) {
+        Some(xml) => Ok(Bytes::from(xml.into_owned())),
+        None => Err(XmlErrorKind::MalformedEncoding(encoding.name()).into()),
+    }
+}
+
+/// Checks that a document read by a streaming reader is encoded as UTF-8,
+/// since it can't be transcoded up front.
+pub(crate) fn check_utf8(decl: &BytesDecl<'_>) -> Result<(), XmlError> {
+    let Some(label) = decl.encoding() else {
+        return Ok(());
+    };
+    let label = label?;
+
+    match Encoding::for_label(&label) {
+        Some(encoding) if encoding == UTF_8 => Ok(()),
+        _ => Err(unsupported(&label)),
+    }
+}
+
+fn detect(xml: &[u8]) -> Result<&'static Encoding, XmlError> {
+    match xml {
+        // `<?` encoded as UTF-16 without a byte order mark
+        [0x3C, 0x00, 0x3F, 0x00, ..] => return Ok(UTF_16LE),
+        [0x00, 0x3C, 0x00, 0x3F, ..] => return Ok(UTF_16BE),
+        _ => {}
+    }
+
+    let Some(label) = declared_encoding(xml) else {
+        return Ok(UTF_8);
+    };
+    match Encoding::for_label(label) {
+        // the declaration could be read as ASCII, so the document is
+        // mislabeled and most likely UTF-8
+        Some(encoding) if encoding == UTF_16LE || encoding == UTF_16BE => Ok(UTF_8),
+        Some(encoding) if encoding != REPLACEMENT => Ok(encoding),
+        _ => Err(unsupported(label)),
+    }
+}
+
+/// Reads the `encoding` of the XML declaration in an ASCII-compatible
+/// document.
+fn declared_encoding(xml: &[u8]) -> Option<&[u8]> {
+    let decl = xml.trim_ascii_start().strip_prefix(b"<?xml")?;
+    let decl = &decl[..decl.windows(2).position(|w| w == b"?>")?];
+
+    let index = decl.windows(8).position(|w| w == b"encoding")?;
+    let value = decl[index + 8..]
+        .trim_ascii_start()
+        .strip_prefix(b"=")?
+        .trim_ascii_start();
+    let (&quote, value) = value.split_first()?;
+    if quote != b'"' && quote != b'\'' {
+        return None;
+    }
+
+    Some(&value[..value.iter().position(|&b| b == quote)?])
+}
+
+fn unsupported(label: &[u8]) -> XmlError {
+    XmlErrorKind::UnsupportedEncoding(String::from_utf8_lossy(label).into_owned()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn utf8() -> eyre::Result<()> {
+        let xml = Bytes::from_static(b"\xEF\xBB\xBF<foo>bar</foo>");
+        assert_eq!(to_utf8(xml)?, "<foo>bar</foo>");
+
+        let xml = Bytes::from_static(b"<?xml version=\"1.0\" encoding=\"utf-8\"?><foo/>");
+        assert_eq!(to_utf8(xml.clone())?, xml);
+
+        Ok(())
+    }
+
+    #[test]
+    fn latin1() -> eyre::Result<()> {
+        let xml = Bytes::from_static(b"<?xml version='1.0' encoding='ISO-8859-1'?><foo>\xE4</foo>");
+        assert_eq!(
+            to_utf8(xml)?,
+            "<?xml version='1.0' encoding='ISO-8859-1'?><foo>\u{E4}</foo>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn utf16() -> eyre::Result<()> {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><foo>\u{E4}</foo>";
+
+        let le = xml.encode_utf16().flat_map(u16::to_le_bytes);
+        let with_bom = [0xFF, 0xFE].into_iter().chain(le.clone());
+        assert_eq!(to_utf8(with_bom.collect::<Vec<_>>().into())?, xml);
+        assert_eq!(to_utf8(le.collect::<Vec<_>>().into())?, xml);
+
+        let be = xml.encode_utf16().flat_map(u16::to_be_bytes);
+        let with_bom = [0xFE, 0xFF].into_iter().chain(be);
+        assert_eq!(to_utf8(with_bom.collect::<Vec<_>>().into())?, xml);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported() {
+        let xml = Bytes::from_static(b"<?xml version=\"1.0\" encoding=\"EBCDIC\"?><foo/>");
+        assert!(matches!(
+            to_utf8(xml),
+            Err(XmlError {
+                kind: XmlErrorKind::UnsupportedEncoding(label),
+                ..
+            }) if label == "EBCDIC"
+        ));
+
+        let xml = Bytes::from_static(b"\xFF\xFE<\x00f\x00\x00\xD8");
+        assert!(matches!(
+            to_utf8(xml),
+            Err(XmlError {
+                kind: XmlErrorKind::MalformedEncoding("UTF-16LE"),
+                ..
+            })
+        ));
+    }
+}
@@ -3,6 +3,9 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use bytes::Bytes;
+use bytestring::ByteString;
+
+use crate::ElementName;
 
 /// Alias for `Result<T, Error>`.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -45,10 +48,108 @@ impl std::error::Error for Error {
     }
 }
 
+/// A location in an XML document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// The number of bytes from the start of the document
+    pub offset: usize,
+    /// The line number, starting at 1
+    pub line: usize,
+    /// The column in bytes, starting at 1
+    pub column: usize,
+}
+
+impl Position {
+    /// Calculates the line and column of `offset` in `xml`.
+    pub(crate) fn from_offset(xml: &[u8], offset: usize) -> Self {
+        let before = &xml[..offset.min(xml.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |index| index + 1);
+
+        Self {
+            offset,
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: offset - line_start + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The path from the root of a document to an element, e.g.
+/// `multistatus/response[17]/propstat[0]/prop/getlastmodified`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElementPath(Vec<PathSegment>);
+
+/// An element in an [`ElementPath`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathSegment {
+    pub name: ElementName<ByteString>,
+    /// The position among the siblings with the same name, starting at 0.
+    ///
+    /// This is only set if the parent may contain multiple elements with this
+    /// name, e.g. for `response` and `propstat`, but not for `prop`. When the
+    /// document is read without a schema, e.g. in an [`XmlError`], this
+    /// applies to every element except the root.
+    pub index: Option<usize>,
+}
+
+impl ElementPath {
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub(crate) fn push(&mut self, name: ElementName<ByteString>, index: Option<usize>) {
+        self.0.push(PathSegment { name, index });
+    }
+    pub(crate) fn push_front(&mut self, name: ElementName<ByteString>, index: Option<usize>) {
+        self.0.insert(0, PathSegment { name, index });
+    }
+}
+
+impl std::fmt::Display for ElementPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            f.write_str(&segment.name.local_name)?;
+            if let Some(index) = segment.index {
+                write!(f, "[{index}]")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes e.g. ` (in multistatus/response, line 3, column 5)`.
+fn fmt_context(
+    f: &mut std::fmt::Formatter<'_>,
+    path: &ElementPath,
+    position: Option<&Position>,
+) -> std::fmt::Result {
+    match (path.is_empty(), position) {
+        (true, None) => Ok(()),
+        (true, Some(position)) => write!(f, " ({position})"),
+        (false, None) => write!(f, " (in {path})"),
+        (false, Some(position)) => write!(f, " (in {path}, {position})"),
+    }
+}
+
 /// Returned when extracting an element from a [`Value`](crate::Value) fails.
 #[derive(Debug)]
 pub struct ExtractElementError {
     pub kind: ExtractElementErrorKind,
+    path: ElementPath,
+    position: Option<Position>,
     #[cfg(debug_assertions)]
     location: &'static std::panic::Location<'static>,
 }
@@ -58,6 +159,8 @@ impl ExtractElementError {
     pub fn new(kind: ExtractElementErrorKind) -> Self {
         Self {
             kind,
+            path: ElementPath::default(),
+            position: None,
             #[cfg(debug_assertions)]
             location: std::panic::Location::caller(),
         }
@@ -66,11 +169,34 @@ impl ExtractElementError {
     pub fn other(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
         Self::new(ExtractElementErrorKind::Other(e.into()))
     }
+    /// The path of the element that couldn't be extracted, relative to the
+    /// value that extraction started from.
+    ///
+    /// When using [`FromXml`](crate::FromXml), this starts at the root
+    /// element of the document.
+    pub fn path(&self) -> &ElementPath {
+        &self.path
+    }
+    /// The position of the element that couldn't be extracted, if the
+    /// [`Value`](crate::Value) was read from a document.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+    /// Prepends the element that contains the failed element to the path.
+    pub(crate) fn within(mut self, name: ElementName<ByteString>, index: Option<usize>) -> Self {
+        self.path.push_front(name, index);
+        self
+    }
+    pub(crate) fn at(mut self, position: Option<Position>) -> Self {
+        self.position = position;
+        self
+    }
 }
 
 impl std::fmt::Display for ExtractElementError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.kind)?;
+        fmt_context(f, &self.path, self.position.as_ref())?;
 
         #[cfg(debug_assertions)]
         write!(f, " at {}", self.location)?;
@@ -124,42 +250,112 @@ impl std::fmt::Display for ExtractElementErrorKind {
 
 /// Returned when reading or writing XML failed.
 #[derive(Debug)]
-pub enum XmlError {
-    InvalidNamespace(Bytes),
-    UnexpectedTag,
-    Utf8(std::str::Utf8Error),
-    Xml(quick_xml::Error),
+pub struct XmlError {
+    pub kind: XmlErrorKind,
+    path: ElementPath,
+    position: Option<Position>,
+}
+
+impl XmlError {
+    /// The path of the innermost open element when reading failed.
+    pub fn path(&self) -> &ElementPath {
+        &self.path
+    }
+    /// The position in the document where reading failed.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+    /// The precondition that a server should report when a request body
+    /// couldn't be parsed because of this error, if there is one.
+    pub fn condition(&self) -> Option<crate::elements::Condition> {
+        match self.kind {
+            XmlErrorKind::DocType => Some(crate::elements::Condition::NoExternalEntities),
+            _ => None,
+        }
+    }
+    pub(crate) fn at(mut self, position: Position, path: ElementPath) -> Self {
+        self.position = Some(position);
+        self.path = path;
+        self
+    }
+}
+
+impl From<XmlErrorKind> for XmlError {
+    fn from(kind: XmlErrorKind) -> Self {
+        Self {
+            kind,
+            path: ElementPath::default(),
+            position: None,
+        }
+    }
 }
 
 impl From<std::str::Utf8Error> for XmlError {
     fn from(e: std::str::Utf8Error) -> Self {
-        Self::Utf8(e)
+        XmlErrorKind::Utf8(e).into()
     }
 }
 
 impl From<quick_xml::Error> for XmlError {
     fn from(e: quick_xml::Error) -> Self {
-        Self::Xml(e)
+        XmlErrorKind::Xml(e).into()
     }
 }
 
 impl std::fmt::Display for XmlError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidNamespace(bytes) => write!(f, "invalid namespace declaration: {bytes:?}"),
-            Self::Xml(e) => write!(f, "{e}"),
-            Self::UnexpectedTag => write!(f, "unexpected tag"),
-            Self::Utf8(e) => write!(f, "{e}"),
-        }
+        write!(f, "{}", self.kind)?;
+        fmt_context(f, &self.path, self.position.as_ref())
     }
 }
 
 impl std::error::Error for XmlError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Self::Xml(e) => Some(e),
-            Self::Utf8(e) => Some(e),
+        match &self.kind {
+            XmlErrorKind::Xml(e) => Some(e),
+            XmlErrorKind::Utf8(e) => Some(e),
             _ => None,
         }
     }
 }
+
+/// The reason why reading or writing XML failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum XmlErrorKind {
+    InvalidNamespace(Bytes),
+    UnexpectedTag,
+    UnexpectedEof,
+    /// A limit of the [`ReaderConfig`](crate::read::ReaderConfig) was
+    /// exceeded.
+    LimitExceeded(crate::read::Limit),
+    /// The document contains a document type declaration, which isn't
+    /// allowed in [strict mode](crate::read::ReaderConfig::strict).
+    DocType,
+    /// The document uses an encoding that isn't supported, e.g. because it
+    /// is unknown or because the [`MultistatusReader`](crate::read::MultistatusReader)
+    /// only supports UTF-8.
+    UnsupportedEncoding(String),
+    /// The document isn't valid in its declared or detected encoding.
+    MalformedEncoding(&'static str),
+    Utf8(std::str::Utf8Error),
+    Xml(quick_xml::Error),
+}
+
+impl std::fmt::Display for XmlErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNamespace(bytes) => write!(f, "invalid namespace declaration: {bytes:?}"),
+            Self::Xml(e) => write!(f, "{e}"),
+            Self::UnexpectedTag => write!(f, "unexpected tag"),
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::LimitExceeded(limit) => write!(f, "the document exceeds the maximum {limit}"),
+            Self::DocType => write!(f, "document type declarations are not allowed"),
+            Self::UnsupportedEncoding(label) => write!(f, "unsupported encoding `{label}`"),
+            Self::MalformedEncoding(encoding) => {
+                write!(f, "the document is not valid {encoding}")
+            }
+            Self::Utf8(e) => write!(f, "{e}"),
+        }
+    }
+}
@@ -14,25 +14,47 @@
 //! Instead, this crate uses the [`Element`](crate::Element) trait to define an
 //! element and [`FromXml`](crate::FromXml)/[`IntoXml`](crate::IntoXml) for
 //! (de)serialization.
+//!
+//! With the `derive` feature, `Element`, `TryFrom<&Value>`, `TryFrom<Value>`
+//! and `From<T> for Value` can be derived using `#[derive(Element)]`, e.g. to
+//! define vendor-specific properties.
+//!
+//! With the `serde` feature, [`Value`]s can also be converted from and to
+//! types implementing `serde::Deserialize` and `serde::Serialize`.
 
+#[doc(hidden)]
+pub mod __private;
+pub mod compare;
 mod element;
 pub mod elements;
+mod encoding;
 mod error;
 pub mod properties;
-mod read;
+mod query;
+pub mod read;
+#[cfg(feature = "serde")]
+pub mod serde;
 mod utils;
 mod value;
-mod write;
+pub mod write;
 
 use bytes::{BufMut, Bytes};
 
+use crate::{read::ReaderConfig, write::WriterConfig};
+
 #[doc(no_inline)]
 pub use nonempty;
+#[cfg(feature = "derive")]
+pub use webdav_xml_derive::Element;
 
 pub use self::{
-    element::Element,
-    error::{Error, ExtractElementError, ExtractElementErrorKind, Result, XmlError},
-    value::{Value, ValueMap},
+    element::{Element, ElementName},
+    error::{
+        ElementPath, Error, ExtractElementError, ExtractElementErrorKind, PathSegment, Position,
+        Result, XmlError, XmlErrorKind,
+    },
+    query::{Query, QueryError, QueryErrorKind},
+    value::{Attributes, Node, Value, ValueMap},
 };
 
 /// The default WebDAV namespace
@@ -43,29 +65,48 @@ pub const DAV_PREFIX: &str = "d";
 /// Performs deserialization from XML.
 ///
 /// This trait is automatically implemented for any type which implements the
-/// `Element + for<'v> TryFrom<&'v Value, Error = ExtractElementError>` traits.
-/// As such, `FromXml` shouldn't be implemented directly: [`Element`] and
-/// [`TryFrom<&Value>`] should be implemented instead, and you get the `FromXml`
-/// implementation for free.
+/// `Element + TryFrom<Value, Error = ExtractElementError>` traits. As such,
+/// `FromXml` shouldn't be implemented directly: [`Element`] and
+/// [`TryFrom<Value>`] should be implemented instead, and you get the `FromXml`
+/// implementation for free. The parsed root element is moved into the
+/// conversion, so it doesn't have to be cloned.
+///
+/// Documents that aren't encoded as UTF-8 are transcoded before parsing, so
+/// positions in errors refer to the transcoded document.
 pub trait FromXml: Sized {
-    fn from_xml(xml: impl Into<bytes::Bytes>) -> crate::Result<Self>;
+    fn from_xml_with_config(
+        xml: impl Into<bytes::Bytes>,
+        config: &ReaderConfig,
+    ) -> crate::Result<Self>;
+    fn from_xml(xml: impl Into<bytes::Bytes>) -> crate::Result<Self> {
+        Self::from_xml_with_config(xml, &ReaderConfig::default())
+    }
 }
 
 impl FromXml for Value {
-    fn from_xml(xml: impl Into<bytes::Bytes>) -> crate::Result<Self> {
-        Ok(crate::read::read_xml(xml)?)
+    fn from_xml_with_config(
+        xml: impl Into<bytes::Bytes>,
+        config: &ReaderConfig,
+    ) -> crate::Result<Self> {
+        Ok(crate::read::read_xml(xml, config)?)
     }
 }
 
 impl<E> FromXml for E
 where
-    E: Element + for<'v> TryFrom<&'v Value, Error = ExtractElementError>,
+    E: Element + TryFrom<Value, Error = ExtractElementError>,
 {
-    fn from_xml(xml: impl Into<bytes::Bytes>) -> crate::Result<Self> {
-        Ok(Value::from_xml(xml)?
-            .to_map()?
-            .get::<E>()
-            .required::<E>()??)
+    fn from_xml_with_config(
+        xml: impl Into<bytes::Bytes>,
+        config: &ReaderConfig,
+    ) -> crate::Result<Self> {
+        let (value, xml) = crate::read::read_document(xml, config)?;
+
+        let extract = || value.into_map()?.take::<E>().required::<E>()?;
+        extract().map_err(|e| {
+            let position = crate::read::locate(&xml, e.path());
+            e.at(position).into()
+        })
     }
 }
 
@@ -77,29 +118,37 @@ where
 /// [`Into<Value>`] should be implemented instead, and you get the `IntoXml`
 /// implementation for free.
 pub trait IntoXml: Sized {
-    fn write_xml(self, writer: impl std::io::Write) -> crate::Result<()>;
-    fn into_xml(self) -> crate::Result<Bytes> {
+    fn write_xml_with_config(
+        self,
+        writer: impl std::io::Write,
+        config: &WriterConfig,
+    ) -> crate::Result<()>;
+    fn write_xml(self, writer: impl std::io::Write) -> crate::Result<()> {
+        self.write_xml_with_config(writer, &WriterConfig::default())
+    }
+    fn into_xml_with_config(self, config: &WriterConfig) -> crate::Result<Bytes> {
         let mut xml = bytes::BytesMut::new().writer();
-        self.write_xml(&mut xml)?;
+        self.write_xml_with_config(&mut xml, config)?;
         Ok(xml.into_inner().freeze())
     }
+    fn into_xml(self) -> crate::Result<Bytes> {
+        self.into_xml_with_config(&WriterConfig::default())
+    }
 }
 
 impl<T> IntoXml for T
 where
     T: Element + Into<Value>,
 {
-    fn write_xml(self, writer: impl std::io::Write) -> crate::Result<()> {
-        Ok(crate::write::write_xml::<T>(writer, self.into())?)
+    fn write_xml_with_config(
+        self,
+        writer: impl std::io::Write,
+        config: &WriterConfig,
+    ) -> crate::Result<()> {
+        Ok(crate::write::write_xml::<T>(writer, self.into(), config)?)
     }
 }
 
-/// A type that can't be instantiated.
-///
-/// Prevents not yet implemented types from being instantiated.
-#[derive(Clone, Debug, PartialEq)]
-enum Todo {}
-
 pub(crate) trait OptionExt<T> {
     fn required<E: Element>(self) -> std::result::Result<T, ExtractElementError>;
 }
@@ -27,6 +27,8 @@ impl TryFrom<&Value> for CreationDate {
     }
 }
 
+crate::utils::owned_try_from!(CreationDate);
+
 impl From<CreationDate> for Value {
     fn from(CreationDate(datetime): CreationDate) -> Value {
         datetime.format(&Rfc3339).unwrap().into()
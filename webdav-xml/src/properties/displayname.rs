@@ -25,6 +25,8 @@ impl TryFrom<&Value> for DisplayName {
     }
 }
 
+crate::utils::owned_try_from!(DisplayName);
+
 impl From<DisplayName> for Value {
     fn from(DisplayName(s): DisplayName) -> Value {
         Value::Text(s)
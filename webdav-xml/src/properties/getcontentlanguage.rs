@@ -25,6 +25,8 @@ impl TryFrom<&Value> for ContentLanguage {
     }
 }
 
+crate::utils::owned_try_from!(ContentLanguage);
+
 impl From<ContentLanguage> for Value {
     fn from(ContentLanguage(s): ContentLanguage) -> Value {
         Value::Text(s)
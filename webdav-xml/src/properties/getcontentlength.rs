@@ -26,6 +26,8 @@ impl TryFrom<&Value> for ContentLength {
     }
 }
 
+crate::utils::owned_try_from!(ContentLength);
+
 impl From<ContentLength> for Value {
     fn from(ContentLength(len): ContentLength) -> Value {
         len.to_string().into()
@@ -28,6 +28,8 @@ impl TryFrom<&Value> for ContentType {
     }
 }
 
+crate::utils::owned_try_from!(ContentType);
+
 impl From<ContentType> for Value {
     fn from(ContentType(content_type): ContentType) -> Value {
         content_type.to_string().into()
@@ -25,6 +25,8 @@ impl TryFrom<&Value> for ETag {
     }
 }
 
+crate::utils::owned_try_from!(ETag);
+
 impl From<ETag> for Value {
     fn from(ETag(s): ETag) -> Value {
         Value::Text(s)
@@ -28,6 +28,8 @@ impl TryFrom<&Value> for LastModified {
     }
 }
 
+crate::utils::owned_try_from!(LastModified);
+
 impl From<LastModified> for Value {
     fn from(LastModified(date): LastModified) -> Value {
         date.to_string().into()
@@ -2,14 +2,17 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::{Element, ExtractElementError, Todo, Value, DAV_NAMESPACE, DAV_PREFIX};
+use nonempty::NonEmpty;
+
+use crate::{
+    elements::ActiveLock, value::ValueMap, Element, ExtractElementError, Value, DAV_NAMESPACE,
+    DAV_PREFIX,
+};
 
-/// Not yet implemented.
-///
 /// The `lockdiscovery` property as defined in
 /// [RFC 4918](http://webdav.org/specs/rfc4918.html#PROPERTY_lockdiscovery).
-#[derive(Clone, Debug, PartialEq)]
-pub struct LockDiscovery(Todo);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LockDiscovery(pub Vec<ActiveLock>);
 
 impl Element for LockDiscovery {
     const NAMESPACE: &'static str = DAV_NAMESPACE;
@@ -20,13 +23,159 @@ impl Element for LockDiscovery {
 impl TryFrom<&Value> for LockDiscovery {
     type Error = ExtractElementError;
 
-    fn try_from(_: &Value) -> Result<Self, Self::Error> {
-        todo!()
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.without_attributes() {
+            Value::Empty => Ok(Self::default()),
+            value => Ok(Self(value.to_map()?.iter_all().collect::<Result<_, _>>()?)),
+        }
     }
 }
 
+crate::utils::owned_try_from!(LockDiscovery);
+
 impl From<LockDiscovery> for Value {
-    fn from(_: LockDiscovery) -> Value {
-        todo!()
+    fn from(LockDiscovery(activelock): LockDiscovery) -> Value {
+        match NonEmpty::collect(activelock.into_iter().map(Value::from)) {
+            Some(activelock) => {
+                let mut map = ValueMap::new();
+                map.insert::<ActiveLock>(Value::List(Box::new(activelock)));
+                Value::Map(map)
+            }
+            None => Value::Empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        elements::{Depth, LockRoot, LockScope, LockToken, LockType, Owner, Timeout},
+        utils::{test_deserialize, test_serialize},
+    };
+
+    use super::*;
+
+    #[test]
+    fn empty() -> eyre::Result<()> {
+        let xml = r#"<d:lockdiscovery xmlns:d="DAV:"/>"#;
+        let lockdiscovery = LockDiscovery::default();
+
+        test_deserialize(&lockdiscovery, xml)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn activelock() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---retrieving-dav-lockdiscovery
+        let xml = r#"
+<d:lockdiscovery xmlns:d="DAV:">
+  <d:activelock>
+    <d:lockscope>
+      <d:exclusive/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+    <d:depth>0</d:depth>
+    <d:owner>Jane Smith</d:owner>
+    <d:timeout>Infinite</d:timeout>
+    <d:locktoken>
+      <d:href>urn:uuid:f81de2ad-7f3d-a1b2-4f3c-00a0c91a9d76</d:href>
+    </d:locktoken>
+    <d:lockroot>
+      <d:href>http://www.example.com/container/</d:href>
+    </d:lockroot>
+  </d:activelock>
+</d:lockdiscovery>
+        "#;
+        let lockdiscovery = LockDiscovery(vec![ActiveLock {
+            lockscope: LockScope::Exclusive,
+            locktype: LockType::Write,
+            depth: Depth::Zero,
+            owner: Some(Owner(Value::Text("Jane Smith".into()))),
+            timeout: Some(Timeout::Infinite),
+            locktoken: Some(LockToken(
+                "urn:uuid:f81de2ad-7f3d-a1b2-4f3c-00a0c91a9d76".into(),
+            )),
+            lockroot: LockRoot("http://www.example.com/container/".parse()?),
+        }]);
+
+        test_deserialize(&lockdiscovery, xml)?;
+        test_serialize(xml, lockdiscovery)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_activelocks() -> eyre::Result<()> {
+        let xml = r#"
+<d:lockdiscovery xmlns:d="DAV:">
+  <d:activelock>
+    <d:lockscope>
+      <d:shared/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+    <d:depth>infinity</d:depth>
+    <d:owner>
+      <d:href>http://example.org/~ejw/contact.html</d:href>
+    </d:owner>
+    <d:timeout>Second-604800</d:timeout>
+    <d:locktoken>
+      <d:href>urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4</d:href>
+    </d:locktoken>
+    <d:lockroot>
+      <d:href>http://example.com/workspace/webdav/proposal.doc</d:href>
+    </d:lockroot>
+  </d:activelock>
+  <d:activelock>
+    <d:lockscope>
+      <d:shared/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+    <d:depth>0</d:depth>
+    <d:lockroot>
+      <d:href>http://example.com/workspace/webdav/proposal.doc</d:href>
+    </d:lockroot>
+  </d:activelock>
+</d:lockdiscovery>
+        "#;
+        let lockdiscovery = LockDiscovery(vec![
+            ActiveLock {
+                lockscope: LockScope::Shared,
+                locktype: LockType::Write,
+                depth: Depth::Infinity,
+                owner: Some(Owner(Value::Map({
+                    let mut map = ValueMap::new();
+                    map.insert::<crate::elements::Href>(Value::Text(
+                        "http://example.org/~ejw/contact.html".into(),
+                    ));
+                    map
+                }))),
+                timeout: Some(Timeout::Seconds(604800)),
+                locktoken: Some(LockToken(
+                    "urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4".into(),
+                )),
+                lockroot: LockRoot("http://example.com/workspace/webdav/proposal.doc".parse()?),
+            },
+            ActiveLock {
+                lockscope: LockScope::Shared,
+                locktype: LockType::Write,
+                depth: Depth::Zero,
+                owner: None,
+                timeout: None,
+                locktoken: None,
+                lockroot: LockRoot("http://example.com/workspace/webdav/proposal.doc".parse()?),
+            },
+        ]);
+
+        test_deserialize(&lockdiscovery, xml)?;
+        test_serialize(xml, lockdiscovery)?;
+
+        Ok(())
     }
 }
@@ -13,9 +13,14 @@ mod getcontenttype;
 mod getetag;
 mod getlastmodified;
 mod lockdiscovery;
+mod registry;
 mod resourcetype;
 mod supportedlock;
 
+use bytestring::ByteString;
+
+use crate::element::ElementName;
+
 pub use self::{
     creationdate::CreationDate,
     displayname::DisplayName,
@@ -25,6 +30,21 @@ pub use self::{
     getetag::ETag,
     getlastmodified::LastModified,
     lockdiscovery::LockDiscovery,
+    registry::{DecodedProperties, PropertyRegistry},
     resourcetype::{Collection, ResourceType},
     supportedlock::SupportedLock,
 };
+
+/// Whether `name` is one of the properties defined in RFC 4918.
+pub(crate) fn is_rfc4918(name: &ElementName<ByteString>) -> bool {
+    name.is::<CreationDate>()
+        || name.is::<DisplayName>()
+        || name.is::<ContentLanguage>()
+        || name.is::<ContentLength>()
+        || name.is::<ContentType>()
+        || name.is::<ETag>()
+        || name.is::<LastModified>()
+        || name.is::<LockDiscovery>()
+        || name.is::<ResourceType>()
+        || name.is::<SupportedLock>()
+}
@@ -0,0 +1,274 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::any::{Any, TypeId};
+
+use bytestring::ByteString;
+use indexmap::IndexMap;
+
+use crate::{
+    element::{ElementExt, ElementName},
+    elements::Properties,
+    properties::{
+        ContentLanguage, ContentLength, ContentType, CreationDate, DisplayName, ETag, LastModified,
+        LockDiscovery, ResourceType, SupportedLock,
+    },
+    Element, ExtractElementError, Value,
+};
+
+type DecodedValue = Box<dyn Any + Send + Sync>;
+type Decoder = fn(&Value) -> Result<DecodedValue, ExtractElementError>;
+
+/// A set of property types that [`Properties`] can be decoded into.
+///
+/// This allows applications to work with their own properties, e.g. ones
+/// in a vendor-specific namespace, like with the properties defined in RFC
+/// 4918.
+///
+/// ```
+/// use webdav_xml::{
+///     elements::Properties,
+///     properties::{DisplayName, PropertyRegistry},
+///     FromXml,
+/// };
+///
+/// let registry = PropertyRegistry::rfc4918();
+/// let prop = Properties::from_xml(
+///     r#"<d:prop xmlns:d="DAV:" xmlns:x="http://example.com/ns">
+///         <d:displayname>Example</d:displayname>
+///         <d:getcontentlength>unknown</d:getcontentlength>
+///         <x:color>red</x:color>
+///     </d:prop>"#,
+/// )?;
+///
+/// let decoded = registry.decode(&prop);
+/// assert_eq!(
+///     decoded.get::<DisplayName>(),
+///     Some(Some(&DisplayName("Example".into())))
+/// );
+/// assert_eq!(decoded.errors().count(), 1);
+/// assert_eq!(decoded.unknown().names().count(), 1);
+/// # Ok::<(), webdav_xml::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PropertyRegistry {
+    decoders: IndexMap<ElementName<ByteString>, (TypeId, Decoder)>,
+}
+
+impl PropertyRegistry {
+    /// Create a registry without any properties.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a registry with the properties defined in RFC 4918.
+    pub fn rfc4918() -> Self {
+        Self::new()
+            .register::<CreationDate>()
+            .register::<DisplayName>()
+            .register::<ContentLanguage>()
+            .register::<ContentLength>()
+            .register::<ContentType>()
+            .register::<ETag>()
+            .register::<LastModified>()
+            .register::<LockDiscovery>()
+            .register::<ResourceType>()
+            .register::<SupportedLock>()
+    }
+    /// Add a property type to the registry.
+    ///
+    /// If another type was registered for the same element, it is replaced.
+    pub fn register<P>(mut self) -> Self
+    where
+        P: Element
+            + for<'v> TryFrom<&'v Value, Error = ExtractElementError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        fn decode<P>(value: &Value) -> Result<DecodedValue, ExtractElementError>
+        where
+            P: Element
+                + for<'v> TryFrom<&'v Value, Error = ExtractElementError>
+                + Send
+                + Sync
+                + 'static,
+        {
+            match P::try_from(value) {
+                Ok(property) => Ok(Box::new(property)),
+                Err(e) => Err(e.within(P::element_name(), None)),
+            }
+        }
+
+        self.decoders
+            .insert(P::element_name(), (TypeId::of::<P>(), decode::<P>));
+        self
+    }
+    /// Decode all registered properties.
+    ///
+    /// A property that fails to decode doesn't affect the other properties,
+    /// its error is available using [`DecodedProperties::errors()`].
+    pub fn decode(&self, properties: &Properties) -> DecodedProperties {
+        let mut decoded = DecodedProperties::default();
+
+        for name in properties.names() {
+            let value = properties
+                .get_raw(name)
+                .expect("the name was returned by `Properties::names()`");
+
+            let Some((type_id, decode)) = self.decoders.get(name) else {
+                decoded.unknown.insert_raw(name.clone(), value.clone());
+                continue;
+            };
+            if value.is_empty() {
+                decoded.values.insert(*type_id, (name.clone(), None));
+                continue;
+            }
+            match decode(value) {
+                Ok(property) => {
+                    decoded
+                        .values
+                        .insert(*type_id, (name.clone(), Some(property)));
+                }
+                Err(e) => decoded.errors.push((name.clone(), e)),
+            }
+        }
+
+        decoded
+    }
+}
+
+/// The properties of a `prop` element, decoded using a [`PropertyRegistry`].
+#[derive(Default)]
+pub struct DecodedProperties {
+    values: IndexMap<TypeId, (ElementName<ByteString>, Option<DecodedValue>)>,
+    errors: Vec<(ElementName<ByteString>, ExtractElementError)>,
+    unknown: Properties,
+}
+
+impl DecodedProperties {
+    /// Get a decoded property.
+    ///
+    /// Returns
+    /// - `None` if the property doesn't exist or couldn't be decoded
+    /// - `Some(None)` if the property exists and is empty
+    /// - `Some(Some(_))` if the property exists and was successfully decoded
+    pub fn get<P: Element + 'static>(&self) -> Option<Option<&P>> {
+        let (_, property) = self.values.get(&TypeId::of::<P>())?;
+
+        Some(property.as_ref().map(|property| {
+            property
+                .downcast_ref()
+                .expect("properties are stored by their type id")
+        }))
+    }
+    /// Remove a decoded property, see [`DecodedProperties::get()`] for an
+    /// overview of the possible return values.
+    pub fn take<P: Element + 'static>(&mut self) -> Option<Option<P>> {
+        let (_, property) = self.values.shift_remove(&TypeId::of::<P>())?;
+
+        Some(property.map(|property| {
+            *property
+                .downcast()
+                .expect("properties are stored by their type id")
+        }))
+    }
+    /// List the names of the successfully decoded properties.
+    pub fn names(&self) -> impl Iterator<Item = &ElementName<ByteString>> {
+        self.values.values().map(|(name, _)| name)
+    }
+    /// The properties that are registered but couldn't be decoded.
+    pub fn errors(&self) -> impl Iterator<Item = (&ElementName<ByteString>, &ExtractElementError)> {
+        self.errors.iter().map(|(name, e)| (name, e))
+    }
+    /// The properties that aren't registered.
+    pub fn unknown(&self) -> &Properties {
+        &self.unknown
+    }
+}
+
+impl std::fmt::Debug for DecodedProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DecodedProperties")
+            .field("names", &self.names().collect::<Vec<_>>())
+            .field("errors", &self.errors)
+            .field("unknown", &self.unknown)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::FromXml;
+
+    #[derive(Debug, PartialEq)]
+    struct FileId(u64);
+
+    impl Element for FileId {
+        const NAMESPACE: &'static str = "http://owncloud.org/ns";
+        const PREFIX: &'static str = "oc";
+        const LOCAL_NAME: &'static str = "fileid";
+    }
+
+    impl TryFrom<&Value> for FileId {
+        type Error = ExtractElementError;
+
+        fn try_from(value: &Value) -> Result<Self, Self::Error> {
+            value
+                .to_text()?
+                .parse()
+                .map(Self)
+                .map_err(ExtractElementError::other)
+        }
+    }
+
+    #[test]
+    fn decode() -> eyre::Result<()> {
+        let xml = r#"
+        <d:prop xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns" xmlns:x="http://example.com/ns">
+            <d:displayname>Example</d:displayname>
+            <d:getcontentlength>unknown</d:getcontentlength>
+            <d:resourcetype/>
+            <oc:fileid>42</oc:fileid>
+            <x:color>red</x:color>
+        </d:prop>
+        "#;
+        let registry = PropertyRegistry::rfc4918().register::<FileId>();
+        let mut decoded = registry.decode(&Properties::from_xml(xml)?);
+
+        assert_eq!(
+            decoded.get::<DisplayName>(),
+            Some(Some(&DisplayName("Example".into())))
+        );
+        assert_eq!(decoded.get::<ContentLength>(), None);
+        assert_eq!(decoded.get::<ResourceType>(), Some(None));
+        assert_eq!(decoded.get::<ETag>(), None);
+        assert_eq!(decoded.take::<FileId>(), Some(Some(FileId(42))));
+        assert_eq!(decoded.get::<FileId>(), None);
+
+        let errors: Vec<_> = decoded.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, &ContentLength::element_name::<ByteString>());
+        assert_eq!(errors[0].1.path().to_string(), "getcontentlength");
+
+        let color = ElementName {
+            namespace: Some("http://example.com/ns".into()),
+            prefix: Some("x".into()),
+            local_name: "color".into(),
+        };
+        assert_eq!(
+            decoded.unknown(),
+            &Properties::new().with_raw(color, Value::Text("red".into()))
+        );
+
+        // properties without a registered type are unknown
+        let decoded = PropertyRegistry::new().decode(&Properties::from_xml(xml)?);
+        assert_eq!(decoded.names().count(), 0);
+        assert_eq!(decoded.unknown().names().count(), 5);
+
+        Ok(())
+    }
+}
@@ -52,6 +52,8 @@ impl TryFrom<&Value> for ResourceType {
     }
 }
 
+crate::utils::owned_try_from!(ResourceType);
+
 impl From<ResourceType> for Value {
     fn from(ResourceType(map): ResourceType) -> Value {
         Value::Map(map)
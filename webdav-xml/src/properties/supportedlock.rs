@@ -2,14 +2,17 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::{Element, ExtractElementError, Todo, Value, DAV_NAMESPACE, DAV_PREFIX};
+use nonempty::NonEmpty;
+
+use crate::{
+    elements::LockEntry, value::ValueMap, Element, ExtractElementError, Value, DAV_NAMESPACE,
+    DAV_PREFIX,
+};
 
-/// Not yet implemented.
-///
 /// The `supportedlock` property as defined in
 /// [RFC 4918](http://webdav.org/specs/rfc4918.html#PROPERTY_supportedlock).
-#[derive(Clone, Debug, PartialEq)]
-pub struct SupportedLock(Todo);
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SupportedLock(pub Vec<LockEntry>);
 
 impl Element for SupportedLock {
     const NAMESPACE: &'static str = DAV_NAMESPACE;
@@ -20,13 +23,85 @@ impl Element for SupportedLock {
 impl TryFrom<&Value> for SupportedLock {
     type Error = ExtractElementError;
 
-    fn try_from(_: &Value) -> Result<Self, Self::Error> {
-        todo!()
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.without_attributes() {
+            Value::Empty => Ok(Self::default()),
+            value => Ok(Self(value.to_map()?.iter_all().collect::<Result<_, _>>()?)),
+        }
     }
 }
 
+crate::utils::owned_try_from!(SupportedLock);
+
 impl From<SupportedLock> for Value {
-    fn from(_: SupportedLock) -> Value {
-        todo!()
+    fn from(SupportedLock(lockentry): SupportedLock) -> Value {
+        match NonEmpty::collect(lockentry.into_iter().map(Value::from)) {
+            Some(lockentry) => {
+                let mut map = ValueMap::new();
+                map.insert::<LockEntry>(Value::List(Box::new(lockentry)));
+                Value::Map(map)
+            }
+            None => Value::Empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        elements::{LockScope, LockType},
+        utils::{test_deserialize, test_serialize},
+    };
+
+    use super::*;
+
+    #[test]
+    fn empty() -> eyre::Result<()> {
+        let xml = r#"<d:supportedlock xmlns:d="DAV:"/>"#;
+        let supportedlock = SupportedLock::default();
+
+        test_deserialize(&supportedlock, xml)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn lockentries() -> eyre::Result<()> {
+        // http://webdav.org/specs/rfc4918.html#n-example---retrieving-dav-supportedlock
+        let xml = r#"
+<d:supportedlock xmlns:d="DAV:">
+  <d:lockentry>
+    <d:lockscope>
+      <d:exclusive/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+  </d:lockentry>
+  <d:lockentry>
+    <d:lockscope>
+      <d:shared/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+  </d:lockentry>
+</d:supportedlock>
+        "#;
+        let supportedlock = SupportedLock(vec![
+            LockEntry {
+                lockscope: LockScope::Exclusive,
+                locktype: LockType::Write,
+            },
+            LockEntry {
+                lockscope: LockScope::Shared,
+                locktype: LockType::Write,
+            },
+        ]);
+
+        test_deserialize(&supportedlock, xml)?;
+        test_serialize(xml, supportedlock)?;
+
+        Ok(())
     }
 }
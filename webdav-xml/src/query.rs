@@ -0,0 +1,403 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use bytestring::ByteString;
+
+use crate::{ElementName, Node, Value, ValueMap};
+
+/// A path that selects nested values, e.g. `{DAV:}response/*/{DAV:}href`.
+///
+/// A query consists of steps separated by `/`:
+///
+/// - `{namespace}name` selects the child elements with that name, e.g.
+///   `{DAV:}href`. Elements without a namespace are selected using `{}name`.
+/// - `name` selects the child elements with that local name in any
+///   namespace.
+/// - `*` selects every item of a [`Value::List`]. Other values are selected
+///   as they are, so `{DAV:}response/*` works for one or multiple `response`
+///   elements.
+/// - `[n]` selects the `n`-th item of a [`Value::List`], starting at 0.
+///   Other values are treated like a list with a single item. An index can
+///   also directly follow a name, e.g. `{DAV:}response[2]`.
+///
+/// A name step applies to each item of a list, so `*` is only needed to get
+/// the individual items instead of the list itself.
+///
+/// ```
+/// use webdav_xml::{Query, Value, FromXml};
+///
+/// let value = Value::from_xml(
+///     r#"<d:multistatus xmlns:d="DAV:">
+///         <d:response><d:href>/a</d:href></d:response>
+///         <d:response><d:href>/b</d:href></d:response>
+///     </d:multistatus>"#,
+/// )?;
+/// let hrefs = value.select("{DAV:}multistatus/{DAV:}response/*/{DAV:}href")?;
+/// assert_eq!(hrefs, [&Value::Text("/a".into()), &Value::Text("/b".into())]);
+///
+/// let query: Query = "multistatus/response[1]/href".parse()?;
+/// assert_eq!(query.select(&value), [&Value::Text("/b".into())]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Child(NameTest),
+    Index(usize),
+    Items,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct NameTest {
+    /// `None` matches any namespace, `Some("")` matches no namespace.
+    namespace: Option<ByteString>,
+    local_name: ByteString,
+}
+
+impl NameTest {
+    fn matches(&self, name: &ElementName<ByteString>) -> bool {
+        let namespace_matches = match &self.namespace {
+            None => true,
+            Some(namespace) if namespace.is_empty() => name.namespace.is_none(),
+            Some(namespace) => name.namespace.as_ref() == Some(namespace),
+        };
+        namespace_matches && name.local_name == self.local_name
+    }
+}
+
+impl Query {
+    /// Parse a query, see [`Query`] for the syntax.
+    pub fn new(query: &str) -> Result<Self, QueryError> {
+        Parser { query, offset: 0 }.parse()
+    }
+    /// Select the values that match this query in the content of an
+    /// element.
+    pub fn select<'v>(&self, value: &'v Value) -> Vec<&'v Value> {
+        Self::apply(vec![value], &self.steps)
+    }
+    /// Like [`Query::select()`], but starting at a [`ValueMap`].
+    pub fn select_map<'v>(&self, map: &'v ValueMap) -> Vec<&'v Value> {
+        let Some((Step::Child(name), steps)) = self.steps.split_first() else {
+            unreachable!("queries start with a name")
+        };
+        let mut selected = Vec::new();
+        children_of_map(map, name, &mut selected);
+
+        Self::apply(selected, steps)
+    }
+
+    fn apply<'v>(mut values: Vec<&'v Value>, steps: &[Step]) -> Vec<&'v Value> {
+        for step in steps {
+            let mut selected = Vec::new();
+            for value in values {
+                match step {
+                    Step::Child(name) => children(value, name, &mut selected),
+                    Step::Index(index) => selected.extend(items(value).nth(*index)),
+                    Step::Items => selected.extend(items(value)),
+                }
+            }
+            values = selected;
+        }
+        values
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+fn items(value: &Value) -> impl Iterator<Item = &Value> {
+    let (first, rest) = match value {
+        Value::List(list) => (&list.head, list.tail.as_slice()),
+        value => (value, [].as_slice()),
+    };
+    std::iter::once(first).chain(rest)
+}
+
+fn children<'v>(value: &'v Value, name: &NameTest, selected: &mut Vec<&'v Value>) {
+    match value.without_attributes() {
+        Value::Map(map) => children_of_map(map, name, selected),
+        Value::Mixed(nodes) => selected.extend(nodes.iter().filter_map(|node| match node {
+            Node::Element(element_name, value) if name.matches(element_name) => Some(value),
+            _ => None,
+        })),
+        Value::List(_) => {
+            for item in items(value) {
+                children(item, name, selected);
+            }
+        }
+        Value::Empty | Value::Text(_) | Value::WithAttributes { .. } => (),
+    }
+}
+
+fn children_of_map<'v>(map: &'v ValueMap, name: &NameTest, selected: &mut Vec<&'v Value>) {
+    selected.extend(
+        map.map
+            .iter()
+            .filter(|(element_name, _)| name.matches(element_name))
+            .map(|(_, value)| value),
+    );
+}
+
+impl Value {
+    /// Select nested values using a [`Query`], e.g.
+    /// `{DAV:}response/*/{DAV:}href`.
+    pub fn select(&self, query: &str) -> Result<Vec<&Value>, QueryError> {
+        Ok(Query::new(query)?.select(self))
+    }
+}
+
+impl ValueMap {
+    /// Select nested values using a [`Query`], e.g.
+    /// `{DAV:}response/*/{DAV:}href`.
+    pub fn select(&self, query: &str) -> Result<Vec<&Value>, QueryError> {
+        Ok(Query::new(query)?.select_map(self))
+    }
+}
+
+struct Parser<'q> {
+    query: &'q str,
+    offset: usize,
+}
+
+impl<'q> Parser<'q> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut steps = Vec::new();
+
+        loop {
+            match self.rest().chars().next() {
+                Some('*') if !steps.is_empty() => {
+                    self.offset += 1;
+                    steps.push(Step::Items);
+                }
+                Some('[') if !steps.is_empty() => steps.push(self.parse_index()?),
+                Some('*' | '[') => return Err(self.error(QueryErrorKind::ExpectedName)),
+                Some(_) => {
+                    steps.push(Step::Child(self.parse_name()?));
+                    while self.rest().starts_with('[') {
+                        steps.push(self.parse_index()?);
+                    }
+                }
+                None => return Err(self.error(QueryErrorKind::ExpectedName)),
+            }
+
+            match self.rest().chars().next() {
+                None => break,
+                Some('/') => self.offset += 1,
+                Some(c) => return Err(self.error(QueryErrorKind::UnexpectedCharacter(c))),
+            }
+        }
+
+        Ok(Query { steps })
+    }
+
+    fn rest(&self) -> &'q str {
+        &self.query[self.offset..]
+    }
+
+    fn parse_name(&mut self) -> Result<NameTest, QueryError> {
+        let namespace = match self.rest().strip_prefix('{') {
+            Some(rest) => {
+                let Some((namespace, _)) = rest.split_once('}') else {
+                    return Err(self.error(QueryErrorKind::UnclosedNamespace));
+                };
+                self.offset += namespace.len() + 2;
+                Some(namespace.into())
+            }
+            None => None,
+        };
+
+        let rest = self.rest();
+        let len = rest
+            .find(['/', '[', ']', '{', '}', '*'])
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error(QueryErrorKind::ExpectedName));
+        }
+        let local_name = rest[..len].into();
+        self.offset += len;
+
+        Ok(NameTest {
+            namespace,
+            local_name,
+        })
+    }
+
+    fn parse_index(&mut self) -> Result<Step, QueryError> {
+        let start = self.offset;
+        let Some((index, _)) = self.rest()[1..].split_once(']') else {
+            return Err(self.error(QueryErrorKind::UnclosedIndex));
+        };
+        self.offset += index.len() + 2;
+
+        match index {
+            "*" => Ok(Step::Items),
+            index => index.parse().map(Step::Index).map_err(|_| QueryError {
+                kind: QueryErrorKind::InvalidIndex,
+                offset: start + 1,
+            }),
+        }
+    }
+
+    fn error(&self, kind: QueryErrorKind) -> QueryError {
+        QueryError {
+            kind,
+            offset: self.offset,
+        }
+    }
+}
+
+/// Returned when parsing a [`Query`] fails.
+#[derive(Debug, PartialEq)]
+pub struct QueryError {
+    pub kind: QueryErrorKind,
+    offset: usize,
+}
+
+impl QueryError {
+    /// The number of bytes from the start of the query where parsing
+    /// failed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at offset {})", self.kind, self.offset)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// The reason why parsing a [`Query`] failed.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum QueryErrorKind {
+    /// A step must be an element name, e.g. at the start of the query.
+    ExpectedName,
+    UnclosedNamespace,
+    UnclosedIndex,
+    InvalidIndex,
+    UnexpectedCharacter(char),
+}
+
+impl std::fmt::Display for QueryErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ExpectedName => f.write_str("expected an element name"),
+            Self::UnclosedNamespace => f.write_str("missing `}` after namespace"),
+            Self::UnclosedIndex => f.write_str("missing `]` after index"),
+            Self::InvalidIndex => f.write_str("index must be a number or `*`"),
+            Self::UnexpectedCharacter(c) => write!(f, "unexpected character `{c}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::FromXml;
+
+    const XML: &str = r#"
+    <d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+        <d:response>
+            <d:href>/a</d:href>
+            <d:propstat>
+                <d:prop><oc:fileid>1</oc:fileid></d:prop>
+            </d:propstat>
+        </d:response>
+        <d:response>
+            <d:href>/b</d:href>
+            <d:propstat>
+                <d:prop><oc:fileid>2</oc:fileid><fileid>3</fileid></d:prop>
+            </d:propstat>
+        </d:response>
+    </d:multistatus>
+    "#;
+
+    fn texts(values: Vec<&Value>) -> Vec<&str> {
+        values
+            .into_iter()
+            .map(|value| &**value.to_text().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn select() -> eyre::Result<()> {
+        let value = Value::from_xml(XML)?;
+        let multistatus = value.select("{DAV:}multistatus")?[0].to_map()?;
+
+        assert_eq!(
+            texts(multistatus.select("{DAV:}response/*/{DAV:}href")?),
+            ["/a", "/b"]
+        );
+        assert_eq!(
+            texts(multistatus.select("{DAV:}response/{DAV:}href")?),
+            ["/a", "/b"]
+        );
+        assert_eq!(texts(multistatus.select("response[1]/href")?), ["/b"]);
+        assert_eq!(texts(multistatus.select("response/href[0]")?), ["/a", "/b"]);
+        assert_eq!(
+            texts(multistatus.select("response/propstat/prop/fileid")?),
+            ["1", "2", "3"]
+        );
+        assert_eq!(
+            texts(multistatus.select("response/propstat/prop/{http://owncloud.org/ns}fileid")?),
+            ["1", "2"]
+        );
+        assert_eq!(
+            texts(multistatus.select("response/propstat/prop/{}fileid")?),
+            ["3"]
+        );
+        assert_eq!(multistatus.select("response")?.len(), 1);
+        assert_eq!(multistatus.select("response/*")?.len(), 2);
+        assert_eq!(multistatus.select("response[*]")?.len(), 2);
+        assert!(multistatus.select("response[2]")?.is_empty());
+        assert!(multistatus
+            .select("{http://owncloud.org/ns}response")?
+            .is_empty());
+        assert!(value.select("multistatus/response/href/href")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn select_mixed() -> eyre::Result<()> {
+        let value = Value::from_xml(r#"<p>a <b>b</b> c <b id="2">d</b></p>"#)?;
+        assert_eq!(texts(value.select("p/b")?), ["b", "d"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() {
+        for (query, kind, offset) in [
+            ("", QueryErrorKind::ExpectedName, 0),
+            ("*/href", QueryErrorKind::ExpectedName, 0),
+            ("response/", QueryErrorKind::ExpectedName, 9),
+            ("{DAV:response", QueryErrorKind::UnclosedNamespace, 0),
+            ("{DAV:}", QueryErrorKind::ExpectedName, 6),
+            ("response[1", QueryErrorKind::UnclosedIndex, 8),
+            ("response[-1]", QueryErrorKind::InvalidIndex, 9),
+            ("response*", QueryErrorKind::UnexpectedCharacter('*'), 8),
+        ] {
+            assert_eq!(
+                Query::new(query),
+                Err(QueryError { kind, offset }),
+                "{query}"
+            );
+        }
+    }
+}
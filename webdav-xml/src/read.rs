@@ -2,121 +2,1077 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use std::borrow::Cow;
+//! Incremental XML parsing.
 
+use std::{borrow::Cow, collections::HashSet};
+
+use bytes::Bytes;
 use bytestring::ByteString;
+use quick_xml::{
+    events::{BytesStart, Event},
+    name::{LocalName, QName, ResolveResult},
+    NsReader,
+};
 
-use crate::{element::ElementName, utils::BytesExt, value::ValueMap, Value, XmlError};
+use crate::{
+    element::{ElementExt, ElementName},
+    elements::{Multistatus, Properties, Response, ResponseDescription},
+    error::{ElementPath, Position},
+    utils::BytesExt,
+    value::{Attributes, Node, ValueMap},
+    ExtractElementError, Value, XmlError, XmlErrorKind,
+};
 
-pub(crate) fn read_xml(xml: impl Into<bytes::Bytes>) -> Result<Value, XmlError> {
-    let xml = xml.into();
-    let mut reader = XmlReader::new(&xml);
-    reader.read_into_value(&xml)
+/// Options for parsing XML.
+///
+/// The limits protect against hostile documents, e.g. request bodies sent to
+/// a public server. By default, the nesting depth is limited to
+/// [`DEFAULT_MAX_DEPTH`](Self::DEFAULT_MAX_DEPTH) and the number of elements
+/// to [`DEFAULT_MAX_ELEMENTS`](Self::DEFAULT_MAX_ELEMENTS), other limits are
+/// only enforced if they are set.
+///
+/// A [`MultistatusReader`] applies the limits for the number of elements and
+/// the size to each `response` element instead of the whole document.
+#[derive(Clone, Debug)]
+pub struct ReaderConfig {
+    max_size: Option<usize>,
+    max_depth: Option<usize>,
+    max_elements: Option<usize>,
+    max_text_size: Option<usize>,
+    max_attributes: Option<usize>,
+    max_namespaces: Option<usize>,
+    strict: bool,
 }
-struct XmlReader<'x> {
-    reader: quick_xml::NsReader<&'x [u8]>,
-    last: Option<quick_xml::events::Event<'x>>,
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        Self {
+            max_size: None,
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+            max_elements: Some(Self::DEFAULT_MAX_ELEMENTS),
+            max_text_size: None,
+            max_attributes: None,
+            max_namespaces: None,
+            strict: false,
+        }
+    }
 }
 
-impl<'x> XmlReader<'x> {
-    fn new(xml: &'x [u8]) -> Self {
+impl ReaderConfig {
+    /// The default maximum nesting depth.
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+    /// The default maximum number of elements.
+    pub const DEFAULT_MAX_ELEMENTS: usize = 100_000;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The maximum size of the document in bytes.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+    /// The maximum nesting depth of elements, where the root element has a
+    /// depth of 1.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+    /// The maximum number of elements in the document.
+    pub fn max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = Some(max_elements);
+        self
+    }
+    /// The maximum size of the text between two tags in bytes.
+    ///
+    /// Text that is split into multiple parts, e.g. by CDATA sections or
+    /// comments, is counted as a whole.
+    pub fn max_text_size(mut self, max_text_size: usize) -> Self {
+        self.max_text_size = Some(max_text_size);
+        self
+    }
+    /// The maximum number of attributes of a single element, including
+    /// namespace declarations.
+    pub fn max_attributes(mut self, max_attributes: usize) -> Self {
+        self.max_attributes = Some(max_attributes);
+        self
+    }
+    /// The maximum number of namespace declarations that are in scope at the
+    /// same time.
+    pub fn max_namespaces(mut self, max_namespaces: usize) -> Self {
+        self.max_namespaces = Some(max_namespaces);
+        self
+    }
+    /// Enable or disable strict mode.
+    ///
+    /// In strict mode, documents containing a document type declaration
+    /// (`<!DOCTYPE ...>`), which can declare entities, are rejected with
+    /// [`XmlErrorKind::DocType`], and references to undeclared entities are
+    /// errors. Servers should respond to the former with the
+    /// `no-external-entities` precondition (see [`XmlError::condition()`]).
+    ///
+    /// Otherwise, document type declarations are ignored and references to
+    /// entities other than the predefined ones (e.g. `&foo;`) are kept as
+    /// they are.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
+
+/// A limit of a [`ReaderConfig`] that was exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Size,
+    Depth,
+    Elements,
+    TextSize,
+    Attributes,
+    Namespaces,
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Size => "document size",
+            Self::Depth => "nesting depth",
+            Self::Elements => "number of elements",
+            Self::TextSize => "text size",
+            Self::Attributes => "number of attributes",
+            Self::Namespaces => "number of namespace declarations",
+        })
+    }
+}
+
+/// Enforces the limits and rules of a [`ReaderConfig`] while reading.
+struct Policy {
+    config: ReaderConfig,
+    elements: usize,
+    /// The number of namespace declarations of each open element
+    namespaces: Vec<usize>,
+    namespaces_in_scope: usize,
+    /// The size of the text since the last tag
+    text_size: usize,
+}
+
+impl Policy {
+    fn new(config: &ReaderConfig) -> Self {
         Self {
-            reader: quick_xml::NsReader::from_reader(xml),
-            last: None,
+            config: config.clone(),
+            elements: 0,
+            namespaces: Vec::new(),
+            namespaces_in_scope: 0,
+            text_size: 0,
+        }
+    }
+    fn check(limit: Option<usize>, value: usize, kind: Limit) -> Result<(), XmlError> {
+        match limit {
+            Some(limit) if value > limit => Err(XmlErrorKind::LimitExceeded(kind).into()),
+            _ => Ok(()),
+        }
+    }
+    fn start(&mut self, tag: &BytesStart<'_>, empty: bool) -> Result<(), XmlError> {
+        self.text_size = 0;
+        self.elements += 1;
+        Self::check(self.config.max_elements, self.elements, Limit::Elements)?;
+        Self::check(
+            self.config.max_depth,
+            self.namespaces.len() + 1,
+            Limit::Depth,
+        )?;
+
+        let mut attributes = 0;
+        let mut namespaces = 0;
+        for attribute in tag.attributes().with_checks(false).flatten() {
+            attributes += 1;
+            if attribute.key.as_namespace_binding().is_some() {
+                namespaces += 1;
+            }
+        }
+        Self::check(self.config.max_attributes, attributes, Limit::Attributes)?;
+        Self::check(
+            self.config.max_namespaces,
+            self.namespaces_in_scope + namespaces,
+            Limit::Namespaces,
+        )?;
+
+        if !empty {
+            self.namespaces.push(namespaces);
+            self.namespaces_in_scope += namespaces;
+        }
+
+        Ok(())
+    }
+    fn end(&mut self) {
+        self.text_size = 0;
+        if let Some(namespaces) = self.namespaces.pop() {
+            self.namespaces_in_scope -= namespaces;
         }
     }
-    fn last(&self) -> Option<&quick_xml::events::Event<'x>> {
-        self.last.as_ref()
+    fn size(&self, size: usize) -> Result<(), XmlError> {
+        Self::check(self.config.max_size, size, Limit::Size)
+    }
+    fn text(&mut self, size: usize) -> Result<(), XmlError> {
+        self.text_size += size;
+        Self::check(self.config.max_text_size, self.text_size, Limit::TextSize)
     }
-    fn read_resolved_event(
-        &mut self,
-    ) -> quick_xml::Result<(
-        quick_xml::name::ResolveResult<'_>,
-        quick_xml::events::Event<'_>,
-    )> {
-        let (resolve_result, event) = self.reader.read_resolved_event()?;
-        self.last = Some(event.clone());
-        Ok((resolve_result, event))
+    fn doctype(&self) -> Result<(), XmlError> {
+        match self.config.strict {
+            true => Err(XmlErrorKind::DocType.into()),
+            false => Ok(()),
+        }
     }
+    /// Replaces entity and character references.
+    fn unescape<'r>(&self, raw: &'r [u8]) -> Result<Cow<'r, str>, XmlError> {
+        let raw = std::str::from_utf8(raw)?;
+
+        if self.config.strict {
+            return Ok(quick_xml::escape::unescape(raw).map_err(quick_xml::Error::from)?);
+        }
+        if !raw.contains('&') {
+            return Ok(Cow::Borrowed(raw));
+        }
+
+        let mut unescaped = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(start) = rest.find('&') {
+            unescaped.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = rest.find(';').map_or(rest.len(), |end| end + 1);
+            let reference = &rest[..end];
+            match quick_xml::escape::unescape(reference) {
+                Ok(s) => unescaped.push_str(&s),
+                // unknown entities are kept as they are
+                Err(_) => unescaped.push_str(reference),
+            }
+            rest = &rest[end..];
+        }
+        unescaped.push_str(rest);
 
-    fn read_into_value(&mut self, xml: &bytes::Bytes) -> Result<Value, XmlError> {
-        use quick_xml::{
-            events::{BytesStart, Event},
-            name::ResolveResult,
+        Ok(Cow::Owned(unescaped))
+    }
+}
+
+pub(crate) fn read_xml(xml: impl Into<Bytes>, config: &ReaderConfig) -> Result<Value, XmlError> {
+    read_document(xml, config).map(|(value, _)| value)
+}
+
+/// Like [`read_xml()`], but also returns the document transcoded to UTF-8,
+/// which positions refer to.
+pub(crate) fn read_document(
+    xml: impl Into<Bytes>,
+    config: &ReaderConfig,
+) -> Result<(Value, Bytes), XmlError> {
+    let xml = xml.into();
+    let policy = Policy::new(config);
+    policy.size(xml.len())?;
+
+    let xml = crate::encoding::to_utf8(xml)?;
+    let mut reader = NsReader::from_reader(&*xml);
+    let mut policy = policy;
+    let mut namespaces = Namespaces::default();
+    let mut builder = ValueBuilder::default();
+
+    loop {
+        let offset = reader.buffer_position();
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => event,
+            Err(e) => {
+                let position = Position::from_offset(&xml, reader.buffer_position());
+                return Err(XmlError::from(e).at(position, builder.path()));
+            }
+        };
+        let result =
+            convert_event(&reader, &mut policy, &mut namespaces, &xml, event).and_then(|event| {
+                match event {
+                    Some(event) => builder.push(event),
+                    None => Ok(()),
+                }
+            });
+        if let Err(e) = result {
+            return Err(e.at(Position::from_offset(&xml, offset), builder.path()));
+        }
+    }
+
+    let path = builder.path();
+    match builder.finish() {
+        Ok(value) => Ok((value, xml)),
+        Err(e) => Err(e.at(Position::from_offset(&xml, xml.len()), path)),
+    }
+}
+
+/// Finds the start of the element at `path`, e.g. to report where an
+/// [`ExtractElementError`] occurred.
+pub(crate) fn locate(xml: &[u8], path: &ElementPath) -> Option<Position> {
+    let mut reader = NsReader::from_reader(xml);
+    let mut segments = path.segments().iter();
+    let mut target = segments.next()?;
+    // the number of preceding siblings with the name of the target
+    let mut siblings = 0;
+    // the depth relative to the parent of the target
+    let mut depth = 0;
+
+    loop {
+        let offset = reader.buffer_position();
+        let (tag, empty) = match reader.read_event().ok()? {
+            Event::Start(tag) => (tag, false),
+            Event::Empty(tag) => (tag, true),
+            Event::End(_) if depth == 0 => return None,
+            Event::End(_) => {
+                depth -= 1;
+                continue;
+            }
+            Event::Eof => return None,
+            _ => continue,
         };
 
-        fn key(
-            xml: &bytes::Bytes,
-            resolve_result: &ResolveResult,
-            tag: &BytesStart<'_>,
-        ) -> Result<ElementName<ByteString>, XmlError> {
-            match resolve_result {
-                ResolveResult::Bound(ns) => {
-                    if ns.as_ref().is_empty() {
-                        return Err(XmlError::InvalidNamespace(xml.maybe_slice_ref(ns.as_ref())));
+        if depth == 0 && matches_name(&reader, &tag, &target.name) {
+            if siblings == target.index.unwrap_or(0) {
+                match segments.next() {
+                    Some(next) if !empty => {
+                        target = next;
+                        siblings = 0;
+                        continue;
+                    }
+                    Some(_) => return None,
+                    None => return Some(Position::from_offset(xml, offset)),
+                }
+            }
+            siblings += 1;
+        }
+        if !empty {
+            depth += 1;
+        }
+    }
+}
+
+fn matches_name<R>(
+    reader: &NsReader<R>,
+    tag: &BytesStart<'_>,
+    name: &ElementName<ByteString>,
+) -> bool {
+    match reader.resolve_element(tag.name()) {
+        (ResolveResult::Bound(ns), local_name) => {
+            name.namespace.as_deref().map(str::as_bytes) == Some(ns.as_ref())
+                && name.local_name.as_bytes() == local_name.as_ref()
+        }
+        _ => name.namespace.is_none() && name.local_name.as_bytes() == tag.name().as_ref(),
+    }
+}
+
+/// Reads the `response` elements of a `multistatus` element one at a time.
+///
+/// Unlike [`FromXml`](crate::FromXml), this doesn't need the whole document
+/// in memory, so it can be used for very large responses, e.g. to a
+/// `PROPFIND` request with `Depth: infinity`.
+///
+/// Each [`Response`] is returned as soon as its end tag was read. If the
+/// reader implements [`BufRead`](std::io::BufRead), this is an [`Iterator`].
+/// With the `tokio` feature, [`MultistatusReader::next_response()`] can be
+/// used for readers implementing
+/// [`tokio::io::AsyncBufRead`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncBufRead.html).
+///
+/// Unlike [`FromXml`](crate::FromXml), this only supports UTF-8 documents.
+/// Other encodings are reported as
+/// [`XmlErrorKind::UnsupportedEncoding`](crate::XmlErrorKind::UnsupportedEncoding).
+pub struct MultistatusReader<R> {
+    reader: NsReader<R>,
+    policy: Policy,
+    namespaces: Namespaces,
+    buf: Vec<u8>,
+    state: State,
+    builder: ValueBuilder,
+    responsedescription: Option<ResponseDescription>,
+    lines: LineTracker,
+    /// The position of the last event, used for errors
+    position: Position,
+    /// The depth, name and position of each element of the current child of
+    /// the `multistatus` element, in document order, for locating errors
+    elements: Vec<(usize, ElementName<ByteString>, Position)>,
+    /// The offset of the current child of the `multistatus` element
+    child_offset: usize,
+    responses: usize,
+}
+
+enum State {
+    BeforeRoot,
+    InRoot,
+    Done,
+}
+
+enum Step {
+    Continue,
+    Response(Box<Response>),
+    Done,
+}
+
+impl<R> MultistatusReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, &ReaderConfig::default())
+    }
+    pub fn with_config(reader: R, config: &ReaderConfig) -> Self {
+        let start = LineTracker::default().position(0);
+        Self {
+            reader: NsReader::from_reader(reader),
+            policy: Policy::new(config),
+            namespaces: Namespaces::default(),
+            buf: Vec::new(),
+            state: State::BeforeRoot,
+            builder: ValueBuilder::default(),
+            responsedescription: None,
+            lines: LineTracker::default(),
+            position: start,
+            elements: Vec::new(),
+            child_offset: 0,
+            responses: 0,
+        }
+    }
+    /// The `responsedescription` of the `multistatus` element, if it was
+    /// already read.
+    ///
+    /// This element usually follows the `response` elements, so it is only
+    /// available after all responses were read.
+    pub fn responsedescription(&self) -> Option<&ResponseDescription> {
+        self.responsedescription.as_ref()
+    }
+    fn handle(&mut self, event: Option<ReadEvent>) -> crate::Result<Step> {
+        match (&self.state, event) {
+            (State::Done, _) => Ok(Step::Done),
+            (_, None) => Err(XmlError::from(XmlErrorKind::UnexpectedEof).into()),
+            (State::BeforeRoot, Some(ReadEvent::Text(_))) => Ok(Step::Continue),
+            (State::BeforeRoot, Some(ReadEvent::Start(name, _))) if name.is::<Multistatus>() => {
+                self.state = State::InRoot;
+                Ok(Step::Continue)
+            }
+            (State::BeforeRoot, Some(ReadEvent::Empty(name, _))) if name.is::<Multistatus>() => {
+                self.state = State::Done;
+                Ok(Step::Done)
+            }
+            (State::BeforeRoot, Some(_)) => Err(XmlError::from(XmlErrorKind::UnexpectedTag).into()),
+            (State::InRoot, Some(ReadEvent::End(_))) if self.builder.depth() == 0 => {
+                self.state = State::Done;
+                Ok(Step::Done)
+            }
+            (State::InRoot, Some(ReadEvent::Text(_))) if self.builder.depth() == 0 => {
+                Ok(Step::Continue)
+            }
+            (State::InRoot, Some(event)) => {
+                if let ReadEvent::Start(name, _) | ReadEvent::Empty(name, _) = &event {
+                    let depth = self.builder.depth();
+                    if depth == 0 {
+                        self.elements.clear();
+                        if name.is::<Response>() {
+                            self.responses += 1;
+                        }
                     }
+                    self.elements.push((depth, name.clone(), self.position));
+                }
 
-                    Ok(ElementName {
-                        namespace: Some(xml.maybe_slice_ref(ns.as_ref()).try_into()?),
-                        prefix: None,
-                        local_name: xml.maybe_slice_ref(tag.local_name().as_ref()).try_into()?,
-                    })
+                self.builder.push(event)?;
+                if self.builder.depth() > 0 {
+                    return Ok(Step::Continue);
                 }
-                ResolveResult::Unbound | ResolveResult::Unknown(_) => Ok(ElementName {
-                    namespace: None,
-                    prefix: None,
-                    local_name: xml.maybe_slice_ref(tag.name().as_ref()).try_into()?,
-                }),
+
+                let value = std::mem::take(&mut self.builder).finish()?;
+                let Some((name, value)) = value.into_map()?.map.into_iter().next() else {
+                    return Ok(Step::Continue);
+                };
+                let index = name.is::<Response>().then(|| self.responses - 1);
+                let locate = |e: ExtractElementError| {
+                    let e = e
+                        .within(name.clone(), index)
+                        .within(Multistatus::element_name(), None);
+                    let position = self.element_position(e.path());
+                    e.at(position)
+                };
+
+                if name.is::<Response>() {
+                    let response = Response::try_from(value).map_err(locate)?;
+                    return Ok(Step::Response(Box::new(response)));
+                }
+                if name.is::<ResponseDescription>() {
+                    self.responsedescription =
+                        Some(ResponseDescription::try_from(&value).map_err(locate)?);
+                }
+                Ok(Step::Continue)
             }
         }
+    }
+    /// The path of the innermost open element.
+    fn path(&self) -> ElementPath {
+        let mut path = ElementPath::default();
+        if let State::BeforeRoot = self.state {
+            return path;
+        }
 
-        let mut map = ValueMap::new();
+        path.push(Multistatus::element_name(), None);
+        for (i, segment) in self.builder.path().segments().iter().enumerate() {
+            let index = match i == 0 && segment.name.is::<Response>() {
+                true => Some(self.responses - 1),
+                false => segment.index,
+            };
+            path.push(segment.name.clone(), index);
+        }
+        path
+    }
+    /// The position of the element at `path` within the current child of the
+    /// `multistatus` element, like [`locate()`] for whole documents.
+    fn element_position(&self, path: &ElementPath) -> Option<Position> {
+        let (_, _, mut position) = self.elements.first()?;
+        // the elements in the subtree of the last matched element
+        let mut elements = self.elements.get(1..)?;
 
+        // the first two segments are `multistatus` and the current child
+        for (depth, target) in path.segments().iter().enumerate().skip(2) {
+            let depth = depth - 1;
+            let index = elements
+                .iter()
+                .take_while(|(d, _, _)| *d >= depth)
+                .enumerate()
+                .filter(|(_, (d, name, _))| *d == depth && name == &target.name)
+                .nth(target.index.unwrap_or(0))
+                .map(|(index, _)| index)?;
+            position = elements[index].2;
+            elements = &elements[index + 1..];
+        }
+
+        Some(position)
+    }
+    /// Resets the limits for the number of elements and the size for each
+    /// child of the `multistatus` element.
+    fn start_event(&mut self, offset: usize) {
+        if self.builder.depth() == 0 {
+            self.child_offset = offset;
+            self.policy.elements = 0;
+        }
+    }
+    /// Adds the position and path to an error that occurred while reading.
+    fn locate(&self, e: crate::Error) -> crate::Error {
+        match e {
+            crate::Error::Xml(e) => e.at(self.position, self.path()).into(),
+            e => e,
+        }
+    }
+}
+
+impl<R: std::io::BufRead> MultistatusReader<R> {
+    fn read_event(&mut self) -> Result<Option<ReadEvent>, XmlError> {
         loop {
-            let (resolve_result, event) = self.read_resolved_event()?;
-            // dbg!(&event);
-            match event {
-                Event::Text(text)
-                    if std::str::from_utf8(&text)
-                        .is_ok_and(|s| s.chars().all(char::is_whitespace)) =>
-                {
-                    continue
+            self.buf.clear();
+            let offset = self.reader.buffer_position();
+            self.position = self.lines.position(offset);
+            self.start_event(offset);
+            let event = match self.reader.read_event_into(&mut self.buf) {
+                Ok(event) => event,
+                Err(e) => {
+                    self.position = self.lines.position(self.reader.buffer_position());
+                    return Err(e.into());
                 }
-                Event::Text(text) => {
-                    // TODO: use ByteString and only reallocate when something was escaped
-                    let value = Value::Text(match text.unescape()? {
-                        Cow::Borrowed(s) => xml
-                            .maybe_slice_ref(s.as_bytes())
-                            .try_into()
-                            .expect("string is checked by text.unescape() to be valid"),
-                        Cow::Owned(s) => s.into(),
-                    });
-                    let _ = self.read_resolved_event()?;
-                    return Ok(value);
+            };
+            self.lines.track(offset, &event);
+            self.policy
+                .size(self.reader.buffer_position() - self.child_offset)?;
+            if let Event::Decl(decl) = &event {
+                crate::encoding::check_utf8(decl)?;
+            }
+            if let Event::Eof = event {
+                return Ok(None);
+            }
+            if let Some(event) = convert_event(
+                &self.reader,
+                &mut self.policy,
+                &mut self.namespaces,
+                &Bytes::new(),
+                event,
+            )? {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+impl<R: std::io::BufRead> Iterator for MultistatusReader<R> {
+    type Item = crate::Result<Response>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let State::Done = self.state {
+            return None;
+        }
+        loop {
+            let step = match self.read_event() {
+                Ok(event) => self.handle(event),
+                Err(e) => Err(e.into()),
+            };
+            match step {
+                Ok(Step::Continue) => continue,
+                Ok(Step::Response(response)) => return Some(Ok(*response)),
+                Ok(Step::Done) => return None,
+                Err(e) => {
+                    let e = self.locate(e);
+                    self.state = State::Done;
+                    return Some(Err(e));
                 }
-                Event::Start(start) => {
-                    let key = key(xml, &resolve_result, &start)?;
-                    let start_name = xml.maybe_slice_ref(start.name().as_ref());
-                    drop(resolve_result);
-                    drop(start);
+            }
+        }
+    }
+}
 
-                    map.insert_raw(key, self.read_into_value(xml)?);
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncBufRead + Unpin> MultistatusReader<R> {
+    /// Read the next `response` element.
+    ///
+    /// Returns `None` after the end of the `multistatus` element or after an
+    /// error.
+    pub async fn next_response(&mut self) -> Option<crate::Result<Response>> {
+        if let State::Done = self.state {
+            return None;
+        }
+        loop {
+            let step = match self.read_event_async().await {
+                Ok(event) => self.handle(event),
+                Err(e) => Err(e.into()),
+            };
+            match step {
+                Ok(Step::Continue) => continue,
+                Ok(Step::Response(response)) => return Some(Ok(*response)),
+                Ok(Step::Done) => return None,
+                Err(e) => {
+                    let e = self.locate(e);
+                    self.state = State::Done;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+    async fn read_event_async(&mut self) -> Result<Option<ReadEvent>, XmlError> {
+        loop {
+            self.buf.clear();
+            let offset = self.reader.buffer_position();
+            self.position = self.lines.position(offset);
+            self.start_event(offset);
+            let event = match self.reader.read_event_into_async(&mut self.buf).await {
+                Ok(event) => event,
+                Err(e) => {
+                    self.position = self.lines.position(self.reader.buffer_position());
+                    return Err(e.into());
+                }
+            };
+            self.lines.track(offset, &event);
+            self.policy
+                .size(self.reader.buffer_position() - self.child_offset)?;
+            if let Event::Decl(decl) = &event {
+                crate::encoding::check_utf8(decl)?;
+            }
+            if let Event::Eof = event {
+                return Ok(None);
+            }
+            if let Some(event) = convert_event(
+                &self.reader,
+                &mut self.policy,
+                &mut self.namespaces,
+                &Bytes::new(),
+                event,
+            )? {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
 
-                    if !matches!(self.last(), Some(Event::End(end)) if end.name().as_ref() == start_name)
-                    {
-                        return Err(XmlError::UnexpectedTag);
-                    }
+/// Calculates line numbers while streaming, when the previous parts of the
+/// document are no longer available.
+#[derive(Default)]
+struct LineTracker {
+    /// The number of newlines so far
+    newlines: usize,
+    /// The offset of the first byte after the last newline
+    line_start: usize,
+}
+
+impl LineTracker {
+    fn position(&self, offset: usize) -> Position {
+        Position {
+            offset,
+            line: self.newlines + 1,
+            column: offset.saturating_sub(self.line_start) + 1,
+        }
+    }
+    /// Counts the newlines in an event that started at `offset`.
+    fn track(&mut self, offset: usize, event: &Event<'_>) {
+        // The markup around the content of an event never contains newlines,
+        // so it is enough to know where the content starts.
+        let (markup, content): (usize, &[u8]) = match event {
+            Event::Start(e) | Event::Empty(e) => (1, e),
+            Event::End(e) => (2, e),
+            Event::Text(e) => (0, e),
+            Event::CData(e) => (9, e),
+            Event::Comment(e) => (4, e),
+            Event::Decl(e) => (2, e),
+            Event::PI(e) => (2, e),
+            Event::DocType(e) => (9, e),
+            Event::Eof => return,
+        };
+
+        if let Some(last) = content.iter().rposition(|&b| b == b'\n') {
+            self.newlines += content.iter().filter(|&&b| b == b'\n').count();
+            self.line_start = offset + markup + last + 1;
+        }
+    }
+}
+
+/// The namespaces that were used so far.
+///
+/// Resolved namespaces don't point into the document, so they are copied
+/// once and then shared by all names in the namespace.
+#[derive(Default)]
+struct Namespaces(HashSet<ByteString>);
+
+impl Namespaces {
+    fn get(&mut self, namespace: &[u8]) -> Result<ByteString, XmlError> {
+        let namespace = std::str::from_utf8(namespace)?;
+        if let Some(namespace) = self.0.get(namespace) {
+            return Ok(namespace.clone());
+        }
+
+        let namespace = ByteString::from(namespace);
+        // a streamed document could declare a new namespace in each response
+        if self.0.len() < 64 {
+            self.0.insert(namespace.clone());
+        }
+        Ok(namespace)
+    }
+}
+
+/// An XML event with resolved names, independent of the underlying reader.
+enum ReadEvent {
+    Start(ElementName<ByteString>, Attributes),
+    Empty(ElementName<ByteString>, Attributes),
+    End(ElementName<ByteString>),
+    Text(ByteString),
+}
+
+/// Resolves the names in an event.
+///
+/// Data borrowed from `xml` is sliced without copying, anything else is
+/// copied. Returns `None` for events that don't contribute to a [`Value`],
+/// e.g. comments.
+fn convert_event<R>(
+    reader: &NsReader<R>,
+    policy: &mut Policy,
+    namespaces: &mut Namespaces,
+    xml: &Bytes,
+    event: Event<'_>,
+) -> Result<Option<ReadEvent>, XmlError> {
+    fn name(
+        xml: &Bytes,
+        namespaces: &mut Namespaces,
+        resolve_result: &ResolveResult,
+        qname: QName<'_>,
+        local_name: LocalName<'_>,
+    ) -> Result<ElementName<ByteString>, XmlError> {
+        match resolve_result {
+            ResolveResult::Bound(ns) => {
+                if ns.as_ref().is_empty() {
+                    return Err(
+                        XmlErrorKind::InvalidNamespace(xml.maybe_slice_ref(ns.as_ref())).into(),
+                    );
+                }
+
+                Ok(ElementName {
+                    namespace: Some(namespaces.get(ns.as_ref())?),
+                    prefix: None,
+                    local_name: xml.maybe_slice_ref(local_name.as_ref()).try_into()?,
+                })
+            }
+            ResolveResult::Unbound | ResolveResult::Unknown(_) => Ok(ElementName {
+                namespace: None,
+                prefix: None,
+                local_name: xml.maybe_slice_ref(qname.as_ref()).try_into()?,
+            }),
+        }
+    }
+    fn key<R>(
+        xml: &Bytes,
+        namespaces: &mut Namespaces,
+        reader: &NsReader<R>,
+        qname: QName<'_>,
+    ) -> Result<ElementName<ByteString>, XmlError> {
+        let (resolve_result, local_name) = reader.resolve_element(qname);
+        name(xml, namespaces, &resolve_result, qname, local_name)
+    }
+    fn attributes<R>(
+        xml: &Bytes,
+        namespaces: &mut Namespaces,
+        reader: &NsReader<R>,
+        policy: &Policy,
+        tag: &BytesStart<'_>,
+    ) -> Result<Attributes, XmlError> {
+        let mut attributes = Attributes::new();
+
+        for attribute in tag.attributes() {
+            let attribute = attribute.map_err(quick_xml::Error::from)?;
+            if attribute.key.as_namespace_binding().is_some() {
+                continue;
+            }
+
+            let (resolve_result, local_name) = reader.resolve_attribute(attribute.key);
+            let key = name(xml, namespaces, &resolve_result, attribute.key, local_name)?;
+            let value = match policy.unescape(&attribute.value)? {
+                Cow::Borrowed(s) => xml
+                    .maybe_slice_ref(s.as_bytes())
+                    .try_into()
+                    .expect("string is checked by unescape() to be valid"),
+                Cow::Owned(s) => s.into(),
+            };
+
+            attributes.insert(key, value);
+        }
+
+        Ok(attributes)
+    }
+
+    match &event {
+        Event::Start(start) => policy.start(start, false)?,
+        Event::Empty(tag) => policy.start(tag, true)?,
+        Event::End(_) => policy.end(),
+        Event::Text(text) => policy.text(text.len())?,
+        Event::CData(cdata) => policy.text(cdata.len())?,
+        Event::DocType(_) => policy.doctype()?,
+        _ => {}
+    }
+
+    Ok(Some(match event {
+        Event::Start(start) => ReadEvent::Start(
+            key(xml, namespaces, reader, start.name())?,
+            attributes(xml, namespaces, reader, policy, &start)?,
+        ),
+        Event::Empty(tag) => ReadEvent::Empty(
+            key(xml, namespaces, reader, tag.name())?,
+            attributes(xml, namespaces, reader, policy, &tag)?,
+        ),
+        Event::End(end) => ReadEvent::End(key(xml, namespaces, reader, end.name())?),
+        // TODO: use ByteString and only reallocate when something was escaped
+        Event::Text(text) => ReadEvent::Text(match policy.unescape(&text)? {
+            Cow::Borrowed(s) => xml
+                .maybe_slice_ref(s.as_bytes())
+                .try_into()
+                .expect("string is checked by unescape() to be valid"),
+            Cow::Owned(s) => s.into(),
+        }),
+        Event::CData(cdata) => ReadEvent::Text(
+            match cdata.into_inner() {
+                Cow::Borrowed(b) => xml.maybe_slice_ref(b),
+                Cow::Owned(b) => b.into(),
+            }
+            .try_into()?,
+        ),
+        Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) | Event::Eof => {
+            return Ok(None)
+        }
+    }))
+}
+
+/// Builds a [`Value`] from a sequence of events.
+///
+/// This doesn't use recursion, so events can be pushed one at a time while
+/// they are read.
+#[derive(Default)]
+struct ValueBuilder {
+    /// The elements that are currently open, with their content so far
+    stack: Vec<(ElementName<ByteString>, Attributes, Content)>,
+    /// The content outside of any element
+    content: Content,
+}
+
+impl ValueBuilder {
+    fn depth(&self) -> usize {
+        self.stack.len()
+    }
+    fn current(&mut self) -> &mut Content {
+        match self.stack.last_mut() {
+            Some((_, _, content)) => content,
+            None => &mut self.content,
+        }
+    }
+    fn push(&mut self, event: ReadEvent) -> Result<(), XmlError> {
+        match event {
+            ReadEvent::Start(name, attributes) => {
+                let content = Content {
+                    preserve_whitespace: self.preserves_whitespace(&name),
+                    ..Default::default()
+                };
+                self.stack.push((name, attributes, content))
+            }
+            ReadEvent::Empty(name, attributes) => self
+                .current()
+                .push_element(name, with_attributes(Value::Empty, attributes)),
+            ReadEvent::End(end) => {
+                if !matches!(self.stack.last(), Some((name, _, _)) if *name == end) {
+                    return Err(XmlErrorKind::UnexpectedTag.into());
                 }
-                Event::Empty(tag) => {
-                    map.insert_raw(key(xml, &resolve_result, &tag)?, Value::Empty);
+                let (name, attributes, content) = self.stack.pop().expect("checked above");
+                let value = with_attributes(content.into_value(), attributes);
+                self.current().push_element(name, value);
+            }
+            ReadEvent::Text(text) => self.current().push_text(text),
+        }
+
+        Ok(())
+    }
+    /// Whether whitespace is significant in the element `name` that is about
+    /// to be opened.
+    ///
+    /// This is the case for dead properties, i.e. children of `prop` that
+    /// aren't one of the properties defined in RFC 4918, and anything inside
+    /// them, so that their values are kept exactly as they were written.
+    fn preserves_whitespace(&self, name: &ElementName<ByteString>) -> bool {
+        match self.stack.last() {
+            Some((parent, _, content)) => {
+                content.preserve_whitespace
+                    || (parent.is::<Properties>() && !crate::properties::is_rfc4918(name))
+            }
+            None => false,
+        }
+    }
+    fn finish(self) -> Result<Value, XmlError> {
+        if !self.stack.is_empty() {
+            return Err(XmlErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(self.content.into_value())
+    }
+    /// The path of the innermost open element.
+    fn path(&self) -> ElementPath {
+        let mut path = ElementPath::default();
+        let mut siblings = self.content.nodes();
+        for (i, (name, _, content)) in self.stack.iter().enumerate() {
+            let index = siblings
+                .iter()
+                .filter(|node| matches!(node, Node::Element(sibling, _) if sibling == name))
+                .count();
+            // without a schema, any element except the root may be repeated
+            path.push(name.clone(), (i > 0).then_some(index));
+            siblings = content.nodes();
+        }
+        path
+    }
+}
+
+/// The content of an element while it is being read.
+#[derive(Default)]
+struct Content {
+    nodes: Vec<Node>,
+    /// The text after the last child element
+    ///
+    /// It is only added to `nodes` when the next element starts, so adjacent
+    /// parts of the text can be merged.
+    text: Text,
+    /// Whether whitespace between child elements is kept
+    preserve_whitespace: bool,
+}
+
+/// Text that may be read in multiple parts, e.g. when text is followed by a
+/// CDATA section.
+///
+/// Most text consists of a single part, which is stored without copying it.
+#[derive(Default)]
+enum Text {
+    #[default]
+    None,
+    Single(ByteString),
+    Buffer(String),
+}
+
+impl Text {
+    fn push(&mut self, text: ByteString) {
+        *self = match std::mem::take(self) {
+            Self::None => Self::Single(text),
+            Self::Single(first) => {
+                let mut buffer = String::with_capacity(first.len() + text.len());
+                buffer.push_str(&first);
+                buffer.push_str(&text);
+                Self::Buffer(buffer)
+            }
+            Self::Buffer(mut buffer) => {
+                buffer.push_str(&text);
+                Self::Buffer(buffer)
+            }
+        }
+    }
+    fn take(&mut self) -> Option<ByteString> {
+        match std::mem::take(self) {
+            Self::None => None,
+            Self::Single(text) => Some(text),
+            Self::Buffer(buffer) => Some(buffer.into()),
+        }
+    }
+}
+
+impl Content {
+    fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    fn push_text(&mut self, text: ByteString) {
+        self.text.push(text);
+    }
+    fn push_element(&mut self, name: ElementName<ByteString>, value: Value) {
+        if let Some(text) = self.text.take() {
+            self.nodes.push(Node::Text(text));
+        }
+        self.nodes.push(Node::Element(name, value));
+    }
+    /// Converts the content into the most specific [`Value`].
+    ///
+    /// Whitespace between child elements is insignificant unless the element
+    /// also contains other text, i.e. mixed content, or whitespace is
+    /// preserved.
+    fn into_value(self) -> Value {
+        fn is_whitespace(text: &str) -> bool {
+            text.chars().all(char::is_whitespace)
+        }
+
+        let Self {
+            mut nodes,
+            mut text,
+            preserve_whitespace,
+        } = self;
+
+        if nodes.is_empty() {
+            return match text.take() {
+                Some(text) if !is_whitespace(&text) => Value::Text(text),
+                _ => Value::Map(ValueMap::new()),
+            };
+        }
+        nodes.extend(text.take().map(Node::Text));
+
+        let has_text = nodes.iter().any(
+            |node| matches!(node, Node::Text(text) if preserve_whitespace || !is_whitespace(text)),
+        );
+
+        match has_text {
+            true => Value::Mixed(nodes),
+            false => {
+                let mut map = ValueMap::new();
+                for node in nodes {
+                    if let Node::Element(key, value) = node {
+                        map.insert_raw(key, value);
+                    }
                 }
-                Event::End(_) | Event::Eof => break,
-                Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => continue,
-                Event::CData(_) => todo!(),
+                Value::Map(map)
             }
         }
+    }
+}
 
-        Ok(Value::Map(map))
+fn with_attributes(value: Value, attributes: Attributes) -> Value {
+    match attributes.is_empty() {
+        true => value,
+        false => Value::WithAttributes {
+            attributes,
+            value: Box::new(value),
+        },
     }
 }
 
@@ -129,7 +1085,7 @@ mod tests {
 
     macro_rules! value_map {
         { $($key:expr => $value:expr),* $(,)? } => {
-            ValueMap(crate::value::InnerValueMap::from([
+            ValueMap::from(crate::value::InnerValueMap::from([
                 $(
                     (
                         crate::element::ElementName { local_name: ByteString::from_static($key), namespace: None, prefix: None },
@@ -146,7 +1102,7 @@ mod tests {
         let value = Value::Map(value_map! {
             "foo" => Value::Empty
         });
-        assert_eq!(value, read_xml(xml)?);
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
         Ok(())
     }
 
@@ -156,7 +1112,7 @@ mod tests {
         let value = Value::Map(value_map! {
             "foo" => Value::Text("bar".into()),
         });
-        assert_eq!(value, read_xml(xml)?);
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
         Ok(())
     }
 
@@ -168,7 +1124,7 @@ mod tests {
                 "bar" => Value::Empty,
             }),
         });
-        assert_eq!(value, read_xml(xml)?);
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
         Ok(())
     }
 
@@ -178,7 +1134,7 @@ mod tests {
         let value = Value::Map(value_map! {
             "foo" => Value::List(Box::new(nonempty![Value::Empty, Value::Empty])),
         });
-        assert_eq!(value, read_xml(xml)?);
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
         Ok(())
     }
 
@@ -189,7 +1145,511 @@ mod tests {
         let value = Value::Map(value_map! {
             "foo" => Value::List(Box::new(nonempty![Value::Empty, Value::Empty, Value::Empty, Value::Empty, Value::Empty])),
         });
-        assert_eq!(value, read_xml(xml)?);
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
+        Ok(())
+    }
+
+    #[test]
+    fn document_order() -> eyre::Result<()> {
+        let xml = r#"<a>1</a><b>2</b><a>3</a>"#;
+        let value = read_xml(xml, &ReaderConfig::default())?;
+        let items = value
+            .to_map()?
+            .iter()
+            .map(|(name, value)| Ok((&*name.local_name, &**value.to_text()?)))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        assert_eq!(items, [("a", "1"), ("b", "2"), ("a", "3")]);
+        Ok(())
+    }
+
+    #[test]
+    fn mixed() -> eyre::Result<()> {
+        let xml = r#"<foo>Hello <b>world</b> <i>!</i></foo>"#;
+        let name = |local_name| crate::element::ElementName {
+            local_name: ByteString::from_static(local_name),
+            namespace: None,
+            prefix: None,
+        };
+        let value = Value::Map(value_map! {
+            "foo" => Value::Mixed(vec![
+                Node::Text("Hello ".into()),
+                Node::Element(name("b"), Value::Text("world".into())),
+                Node::Text(" ".into()),
+                Node::Element(name("i"), Value::Text("!".into())),
+            ]),
+        });
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
+        Ok(())
+    }
+
+    #[test]
+    fn cdata() -> eyre::Result<()> {
+        let xml = r#"<foo>a &amp; <![CDATA[<b>&</b>]]> c</foo>"#;
+        let value = Value::Map(value_map! {
+            "foo" => Value::Text("a & <b>&</b> c".into()),
+        });
+        assert_eq!(value, read_xml(xml, &ReaderConfig::default())?);
+        Ok(())
+    }
+
+    #[test]
+    fn attributes() -> eyre::Result<()> {
+        let xml = r#"<foo xmlns:x="urn:x" a="1" x:b="2" xml:lang="en">bar</foo>"#;
+        let value = read_xml(xml, &ReaderConfig::default())?;
+        let foo = value.to_map()?.iter().next().unwrap().1;
+        assert_eq!(foo.attributes().map(Attributes::len), Some(3));
+        assert_eq!(foo.attribute(None, "a").map(|s| &**s), Some("1"));
+        assert_eq!(foo.attribute(Some("urn:x"), "b").map(|s| &**s), Some("2"));
+        assert_eq!(
+            foo.attribute(Some("http://www.w3.org/XML/1998/namespace"), "lang")
+                .map(|s| &**s),
+            Some("en")
+        );
+        assert_eq!(&**foo.to_text()?, "bar");
+        Ok(())
+    }
+
+    #[test]
+    fn encoding() -> eyre::Result<()> {
+        let xml = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><foo>Gr\xFC\xDFe</foo>";
+        let value = Value::Map(value_map! {
+            "foo" => Value::Text("Gr\u{FC}\u{DF}e".into()),
+        });
+        assert_eq!(value, read_xml(&xml[..], &ReaderConfig::new())?);
+
+        let results = MultistatusReader::new(&xml[..]).collect::<Vec<_>>();
+        assert!(matches!(
+            &results[..],
+            [Err(crate::Error::Xml(XmlError {
+                kind: XmlErrorKind::UnsupportedEncoding(_),
+                ..
+            }))]
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits() {
+        fn read(xml: &'static str, config: ReaderConfig) -> Result<Value, XmlError> {
+            read_xml(xml, &config)
+        }
+
+        let xml = r#"<a xmlns:x="urn:x"><b><c x:y="z">text</c></b><b/></a>"#;
+        assert!(read(xml, ReaderConfig::new()).is_ok());
+
+        assert!(read(xml, ReaderConfig::new().max_size(xml.len())).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_size(xml.len() - 1)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Size),
+                ..
+            })
+        ));
+
+        assert!(read(xml, ReaderConfig::new().max_depth(3)).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_depth(2)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Depth),
+                ..
+            })
+        ));
+
+        assert!(read(xml, ReaderConfig::new().max_elements(4)).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_elements(3)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Elements),
+                ..
+            })
+        ));
+
+        assert!(read(xml, ReaderConfig::new().max_text_size(4)).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_text_size(3)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::TextSize),
+                ..
+            })
+        ));
+
+        assert!(read(xml, ReaderConfig::new().max_attributes(1)).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_attributes(0)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Attributes),
+                ..
+            })
+        ));
+
+        // text that is split into multiple parts is counted as a whole
+        for part in ["<![CDATA[0123456789]]>", "0123456789<!---->"] {
+            let xml = format!("<a>{}</a>", part.repeat(100)).leak();
+            assert!(read(xml, ReaderConfig::new().max_text_size(1000)).is_ok());
+            assert!(matches!(
+                read(xml, ReaderConfig::new().max_text_size(50)),
+                Err(XmlError {
+                    kind: XmlErrorKind::LimitExceeded(Limit::TextSize),
+                    ..
+                })
+            ));
+        }
+        let xml = "<a>01234<b/>56789<!---->01234</a>";
+        assert!(read(xml, ReaderConfig::new().max_text_size(10)).is_ok());
+        assert!(read(xml, ReaderConfig::new().max_text_size(9)).is_err());
+
+        let xml = r#"<a xmlns:x="urn:x"><b xmlns:y="urn:y"/><b xmlns:y="urn:y"/></a>"#;
+        assert!(read(xml, ReaderConfig::new().max_namespaces(2)).is_ok());
+        assert!(matches!(
+            read(xml, ReaderConfig::new().max_namespaces(1)),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Namespaces),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn doctype() -> eyre::Result<()> {
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE foo [<!ENTITY xxe SYSTEM "file:///etc/passwd">]>
+<foo>&xxe;</foo>"#;
+
+        let error = read_xml(xml, &ReaderConfig::new().strict(true)).unwrap_err();
+        assert!(matches!(error.kind, XmlErrorKind::DocType));
+        assert_eq!(
+            error.condition(),
+            Some(crate::elements::Condition::NoExternalEntities)
+        );
+
+        let value = Value::Map(value_map! {
+            "foo" => Value::Text("&xxe;".into()),
+        });
+        assert_eq!(value, read_xml(xml, &ReaderConfig::new())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn entities() -> eyre::Result<()> {
+        let xml = r#"<foo bar="&custom; &amp;">&lt;&#x41;&custom;&gt; &amp</foo>"#;
+
+        let value = read_xml(xml, &ReaderConfig::new())?;
+        let foo = value.to_map()?.iter().next().unwrap().1;
+        assert_eq!(&**foo.to_text()?, "<A&custom;> &amp");
+        assert_eq!(foo.attribute(None, "bar").map(|s| &**s), Some("&custom; &"));
+
+        assert!(read_xml(xml, &ReaderConfig::new().strict(true)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn multistatus_reader_limits() {
+        // the limits apply to each response, the first one has 6 elements
+        let config = ReaderConfig::new().max_elements(6);
+        let results =
+            MultistatusReader::with_config(MULTISTATUS.as_bytes(), &config).collect::<Vec<_>>();
+        assert!(results.len() == 2 && results.iter().all(Result::is_ok));
+
+        let config = ReaderConfig::new().max_elements(5);
+        let results =
+            MultistatusReader::with_config(MULTISTATUS.as_bytes(), &config).collect::<Vec<_>>();
+        assert!(matches!(
+            results[..],
+            [Err(crate::Error::Xml(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Elements),
+                ..
+            }))]
+        ));
+
+        let config = ReaderConfig::new().max_size(200);
+        let results =
+            MultistatusReader::with_config(MULTISTATUS.as_bytes(), &config).collect::<Vec<_>>();
+        assert!(results[0].is_err());
+        let config = ReaderConfig::new().max_size(300);
+        let results =
+            MultistatusReader::with_config(MULTISTATUS.as_bytes(), &config).collect::<Vec<_>>();
+        assert!(results.len() == 2 && results.iter().all(Result::is_ok));
+    }
+
+    #[test]
+    fn default_limits() -> eyre::Result<()> {
+        let depth = 100_000;
+        let xml = "<a>".repeat(depth) + &"</a>".repeat(depth);
+        assert!(matches!(
+            read_xml(xml, &ReaderConfig::default()),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Depth),
+                ..
+            })
+        ));
+
+        let depth = ReaderConfig::DEFAULT_MAX_DEPTH;
+        let xml = "<a>".repeat(depth) + &"</a>".repeat(depth);
+        let value = read_xml(xml, &ReaderConfig::default())?;
+        let mut written = Vec::new();
+        crate::write::write_xml::<crate::elements::Properties>(
+            &mut written,
+            value,
+            &crate::write::WriterConfig::new().compact(),
+        )?;
+        // the `prop` element adds another level
+        let config = ReaderConfig::new().max_depth(depth + 1);
+        assert!(read_xml(written, &config).is_ok());
+
+        let xml = "<a/>".repeat(ReaderConfig::DEFAULT_MAX_ELEMENTS + 1);
+        assert!(matches!(
+            read_xml(xml, &ReaderConfig::default()),
+            Err(XmlError {
+                kind: XmlErrorKind::LimitExceeded(Limit::Elements),
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    const MULTISTATUS: &str = r#"
+    <?xml version="1.0" encoding="utf-8" ?>
+    <D:multistatus xmlns:D="DAV:">
+      <D:response>
+        <D:href>http://www.example.com/container/</D:href>
+        <D:propstat>
+          <D:prop>
+            <D:displayname>Example collection</D:displayname>
+          </D:prop>
+          <D:status>HTTP/1.1 200 OK</D:status>
+        </D:propstat>
+      </D:response>
+      <D:response>
+        <D:href>http://www.example.com/container/front.html</D:href>
+        <D:status>HTTP/1.1 404 Not Found</D:status>
+      </D:response>
+      <D:responsedescription>Some resources are missing</D:responsedescription>
+    </D:multistatus>
+    "#;
+
+    #[test]
+    fn multistatus_reader() -> eyre::Result<()> {
+        use crate::FromXml;
+
+        let multistatus = Multistatus::from_xml(MULTISTATUS)?;
+
+        let mut reader = MultistatusReader::new(MULTISTATUS.as_bytes());
+        let responses = reader.by_ref().collect::<crate::Result<Vec<_>>>()?;
+
+        assert_eq!(responses, multistatus.response);
+        assert_eq!(
+            reader.responsedescription(),
+            multistatus.responsedescription.as_ref()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multistatus_reader_truncated() {
+        let xml = &MULTISTATUS[..MULTISTATUS.find("<D:responsedescription>").unwrap()];
+
+        let results = MultistatusReader::new(xml.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(Result::is_ok));
+        assert!(matches!(
+            results[2],
+            Err(crate::Error::Xml(XmlError {
+                kind: XmlErrorKind::UnexpectedEof,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn error_position() {
+        let xml = "<a>\n  <b/>\n  <b>\n    <c>\n      <d/>\n    </c>\n  </b>\n</a>";
+
+        let error = read_xml(xml, &ReaderConfig::new().max_depth(3)).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            XmlErrorKind::LimitExceeded(Limit::Depth)
+        ));
+        assert_eq!(error.path().to_string(), "a/b[1]/c[0]");
+        assert_eq!(
+            error.position(),
+            Some(Position {
+                offset: 31,
+                line: 5,
+                column: 7
+            })
+        );
+    }
+
+    #[test]
+    fn extract_error_position() {
+        use crate::{FromXml, Position};
+
+        let xml = MULTISTATUS.replace("HTTP/1.1 404 Not Found", "invalid");
+
+        let crate::Error::ExtractElement(error) = Multistatus::from_xml(xml.clone()).unwrap_err()
+        else {
+            panic!("expected an extraction error");
+        };
+        assert_eq!(error.path().to_string(), "multistatus/response[1]/status");
+        assert_eq!(
+            error
+                .position()
+                .map(|Position { line, column, .. }| (line, column)),
+            Some((15, 9))
+        );
+
+        let results = MultistatusReader::new(xml.as_bytes()).collect::<Vec<_>>();
+        let Err(crate::Error::ExtractElement(error)) = &results[1] else {
+            panic!("expected an extraction error");
+        };
+        assert_eq!(error.path().to_string(), "multistatus/response[1]/status");
+        assert_eq!(
+            error
+                .position()
+                .map(|Position { line, column, .. }| (line, column)),
+            Some((15, 9))
+        );
+
+        // both report the position of the failing element, not of its response
+        let xml = MULTISTATUS.replace("HTTP/1.1 200 OK", "invalid");
+        let crate::Error::ExtractElement(expected) =
+            Multistatus::from_xml(xml.clone()).unwrap_err()
+        else {
+            panic!("expected an extraction error");
+        };
+        let results = MultistatusReader::new(xml.as_bytes()).collect::<Vec<_>>();
+        let Err(crate::Error::ExtractElement(error)) = &results[0] else {
+            panic!("expected an extraction error");
+        };
+        assert_eq!(
+            error.path().to_string(),
+            "multistatus/response[0]/propstat[0]/status"
+        );
+        assert_eq!(error.path(), expected.path());
+        assert_eq!(
+            error
+                .position()
+                .map(|Position { line, column, .. }| (line, column)),
+            Some((10, 11))
+        );
+        assert_eq!(error.position(), expected.position());
+    }
+
+    #[test]
+    fn extract_error_position_transcoded() -> eyre::Result<()> {
+        use crate::{
+            elements::{Properties, Propstat},
+            properties::LastModified,
+            Element, FromXml, Position, DAV_NAMESPACE, DAV_PREFIX,
+        };
+
+        /// The `getlastmodified` properties of a `multistatus` element.
+        #[derive(Debug)]
+        struct Dates(Vec<LastModified>);
+
+        impl Element for Dates {
+            const NAMESPACE: &'static str = DAV_NAMESPACE;
+            const PREFIX: &'static str = DAV_PREFIX;
+            const LOCAL_NAME: &'static str = "multistatus";
+        }
+
+        impl TryFrom<Value> for Dates {
+            type Error = ExtractElementError;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                let mut dates = Vec::new();
+                for (i, response) in Multistatus::try_from(value)?.response.iter().enumerate() {
+                    let Response::Propstat { propstat, .. } = response else {
+                        continue;
+                    };
+                    for (j, propstat) in propstat.iter().enumerate() {
+                        if let Some(Some(date)) = propstat.prop.getlastmodified() {
+                            dates.push(date.map_err(|e| {
+                                e.within(Properties::element_name(), None)
+                                    .within(Propstat::element_name(), Some(j))
+                                    .within(Response::element_name(), Some(i))
+                            })?);
+                        }
+                    }
+                }
+                Ok(Self(dates))
+            }
+        }
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-16"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response>
+    <D:href>/f\u{FC}r.txt</D:href>
+    <D:propstat>
+      <D:prop>
+        <D:displayname>f\u{FC}r</D:displayname>
+        <D:getlastmodified>yesterday</D:getlastmodified>
+      </D:prop>
+      <D:status>HTTP/1.1 200 OK</D:status>
+    </D:propstat>
+  </D:response>
+</D:multistatus>"#;
+        let utf16 = |xml: &str| {
+            [0xFF, 0xFE]
+                .into_iter()
+                .chain(xml.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect::<Vec<u8>>()
+        };
+
+        let crate::Error::ExtractElement(error) = Dates::from_xml(utf16(xml)).unwrap_err() else {
+            panic!("expected an extraction error");
+        };
+        assert_eq!(
+            error.path().to_string(),
+            "multistatus/response[0]/propstat[0]/prop/getlastmodified"
+        );
+        assert_eq!(
+            error
+                .position()
+                .map(|Position { line, column, .. }| (line, column)),
+            Some((8, 9))
+        );
+
+        let valid = xml.replace("yesterday", "Mon, 30 Sep 2019 12:13:02 GMT");
+        assert_eq!(Dates::from_xml(utf16(&valid))?.0.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn multistatus_reader_error_position() {
+        let xml = MULTISTATUS.replace("</D:prop>", "</D:propstat>");
+
+        let results = MultistatusReader::new(xml.as_bytes()).collect::<Vec<_>>();
+        let Err(crate::Error::Xml(error)) = &results[0] else {
+            panic!("expected an XML error");
+        };
+        assert_eq!(
+            error.path().to_string(),
+            "multistatus/response[0]/propstat[0]/prop[0]"
+        );
+        assert_eq!(error.position().map(|position| position.line), Some(9));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn multistatus_reader_async() -> eyre::Result<()> {
+        use crate::FromXml;
+
+        let multistatus = Multistatus::from_xml(MULTISTATUS)?;
+
+        let mut reader = MultistatusReader::new(MULTISTATUS.as_bytes());
+        let mut responses = Vec::new();
+        while let Some(response) = reader.next_response().await {
+            responses.push(response?);
+        }
+
+        assert_eq!(responses, multistatus.response);
         Ok(())
     }
 }
@@ -0,0 +1,691 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{fmt::Display, str::FromStr};
+
+use bytestring::ByteString;
+use nonempty::NonEmpty;
+use serde::de::{
+    value::{BorrowedStrDeserializer, StringDeserializer},
+    DeserializeSeed, Deserializer, EnumAccess, Error as _, IntoDeserializer, MapAccess, SeqAccess,
+    Unexpected, VariantAccess, Visitor,
+};
+
+use crate::{ElementName, ExtractElementError, ExtractElementErrorKind, Node, Value, ValueMap};
+
+impl serde::de::Error for ExtractElementError {
+    #[track_caller]
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::other(msg.to_string())
+    }
+    #[track_caller]
+    fn missing_field(field: &'static str) -> Self {
+        Self::new(ExtractElementErrorKind::MissingElement(field))
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(parse(self)?)
+            }
+        )*
+    };
+}
+
+/// Deserializes the content of an element.
+///
+/// - text is parsed as a primitive type, e.g. a number or a string
+/// - child elements are fields of a struct or entries of a map
+/// - repeated child elements are a sequence, while a single child element
+///   is also a sequence with one item
+/// - empty elements are `None`, an empty string, an empty sequence or a
+///   struct without fields
+/// - text or a single child element is an enum variant
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = ExtractElementError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.without_attributes() {
+            Value::Empty => visitor.visit_unit(),
+            Value::Text(text) => visitor.visit_borrowed_str(text),
+            Value::Map(map) => map.deserialize_any(visitor),
+            Value::List(list) => visitor.visit_seq(SeqDeserializer::new(None, list)),
+            value => match value.formatted_elements() {
+                Some(nodes) => visitor.visit_map(NodesDeserializer::new(nodes, &[])),
+                None => Err(Self::Error::invalid_type(unexpected(value), &visitor)),
+            },
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        // the lexical representations of `xs:boolean`
+        match self.to_text()?.trim() {
+            "true" | "1" => visitor.visit_bool(true),
+            "false" | "0" => visitor.visit_bool(false),
+            text => Err(Self::Error::invalid_value(Unexpected::Str(text), &visitor)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.is_empty() {
+            true => visitor.visit_borrowed_str(""),
+            false => visitor.visit_borrowed_str(self.to_text()?),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.is_empty() {
+            true => visitor.visit_borrowed_bytes(b""),
+            false => visitor.visit_borrowed_bytes(self.to_text()?.as_bytes()),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.is_empty() {
+            true => visitor.visit_none(),
+            false => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.without_attributes() {
+            Value::Empty => visitor.visit_unit(),
+            value => Err(Self::Error::invalid_type(unexpected(value), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.without_attributes() {
+            Value::List(list) => visitor.visit_seq(SeqDeserializer::new(None, list)),
+            Value::Empty => visitor.visit_seq(SeqDeserializer::empty()),
+            _ => visitor.visit_seq(SeqDeserializer::single(self)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match (self.is_empty(), self.formatted_elements()) {
+            (true, _) => visitor.visit_map(MapDeserializer::empty()),
+            (false, Some(nodes)) => visitor.visit_map(NodesDeserializer::new(nodes, &[])),
+            (false, None) => self.to_map()?.deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match (self.is_empty(), self.formatted_elements()) {
+            (true, _) => visitor.visit_map(MapDeserializer::empty()),
+            (false, Some(nodes)) => visitor.visit_map(NodesDeserializer::new(nodes, fields)),
+            (false, None) => self.to_map()?.deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.without_attributes() {
+            Value::Text(text) => visitor.visit_enum(text.trim().into_deserializer()),
+            Value::Map(map) => map.deserialize_enum(name, variants, visitor),
+            value => {
+                let mut elements = elements(value.formatted_elements().unwrap_or_default());
+                match (elements.next(), elements.next()) {
+                    (Some((name, value)), None) => visitor.visit_enum(EnumDeserializer {
+                        name,
+                        value,
+                        variants,
+                    }),
+                    _ => Err(Self::Error::invalid_type(unexpected(value), &visitor)),
+                }
+            }
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Deserializes the child elements of an element as a struct or map.
+///
+/// Child elements are named using the Clark notation, e.g.
+/// `{DAV:}displayname`, or just their local name if they don't have a
+/// namespace. When deserializing a struct, a field without a namespace
+/// matches child elements with that local name in any namespace.
+impl<'de> Deserializer<'de> for &'de ValueMap {
+    type Error = ExtractElementError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(MapDeserializer::new(self, &[]))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(MapDeserializer::new(self, fields))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.map.len() {
+            1 => {
+                let (name, value) = self.map.first().expect("the map has one entry");
+                visitor.visit_enum(EnumDeserializer {
+                    name,
+                    value,
+                    variants,
+                })
+            }
+            _ => Err(Self::Error::invalid_type(Unexpected::Map, &visitor)),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+        ignored_any
+    }
+}
+
+fn parse<T>(value: &Value) -> Result<T, ExtractElementError>
+where
+    T: FromStr,
+    T::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    value
+        .to_text()?
+        .trim()
+        .parse()
+        .map_err(ExtractElementError::other)
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value.without_attributes() {
+        Value::Empty => Unexpected::Unit,
+        Value::Text(text) => Unexpected::Str(text),
+        Value::Map(_) => Unexpected::Map,
+        Value::List(_) => Unexpected::Seq,
+        _ => Unexpected::Other("mixed content"),
+    }
+}
+
+/// Checks whether a struct field or enum variant refers to an element.
+fn matches_exactly(field: &str, name: &ElementName<ByteString>) -> bool {
+    let Some((namespace, local_name)) = field
+        .strip_prefix('{')
+        .and_then(|field| field.split_once('}'))
+    else {
+        return false;
+    };
+    name.namespace.as_deref().unwrap_or_default() == namespace && name.local_name == local_name
+}
+
+/// Deserializes the name of an element as one of the given identifiers if
+/// possible.
+fn deserialize_name<'de, S>(
+    seed: S,
+    name: &'de ElementName<ByteString>,
+    identifiers: &'static [&'static str],
+) -> Result<S::Value, ExtractElementError>
+where
+    S: DeserializeSeed<'de>,
+{
+    let identifier = identifiers
+        .iter()
+        .find(|identifier| matches_exactly(identifier, name))
+        .or_else(|| identifiers.iter().find(|&&id| name.local_name == id));
+
+    match (identifier, &name.namespace) {
+        (Some(identifier), _) => seed.deserialize(BorrowedStrDeserializer::new(identifier)),
+        (None, None) => seed.deserialize(BorrowedStrDeserializer::new(&name.local_name)),
+        (None, Some(namespace)) => seed.deserialize(StringDeserializer::new(format!(
+            "{{{namespace}}}{}",
+            name.local_name
+        ))),
+    }
+}
+
+/// Deserializes a child element, where repeated elements are a sequence.
+fn deserialize_child<'de, S>(
+    seed: S,
+    name: &'de ElementName<ByteString>,
+    value: &'de Value,
+) -> Result<S::Value, ExtractElementError>
+where
+    S: DeserializeSeed<'de>,
+{
+    match value {
+        Value::List(list) => seed.deserialize(ListDeserializer { name, list }),
+        value => seed
+            .deserialize(value)
+            .map_err(|e| e.within(name.clone(), None)),
+    }
+}
+
+struct MapDeserializer<'de> {
+    iter: Option<indexmap::map::Iter<'de, ElementName<ByteString>, Value>>,
+    fields: &'static [&'static str],
+    entry: Option<(&'de ElementName<ByteString>, &'de Value)>,
+}
+
+impl<'de> MapDeserializer<'de> {
+    fn new(map: &'de ValueMap, fields: &'static [&'static str]) -> Self {
+        Self {
+            iter: Some(map.map.iter()),
+            fields,
+            entry: None,
+        }
+    }
+    fn empty() -> Self {
+        Self {
+            iter: None,
+            fields: &[],
+            entry: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((name, value)) = self.iter.as_mut().and_then(Iterator::next) else {
+            return Ok(None);
+        };
+        self.entry = Some((name, value));
+
+        deserialize_name(seed, name, self.fields).map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        let (name, value) = self
+            .entry
+            .take()
+            .expect("`next_value_seed` is called after `next_key_seed`");
+
+        deserialize_child(seed, name, value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter.as_ref().map(ExactSizeIterator::len)
+    }
+}
+
+struct SeqDeserializer<'de> {
+    name: Option<&'de ElementName<ByteString>>,
+    iter: std::iter::Enumerate<
+        std::iter::Chain<std::option::IntoIter<&'de Value>, std::slice::Iter<'de, Value>>,
+    >,
+}
+
+impl<'de> SeqDeserializer<'de> {
+    fn new(name: Option<&'de ElementName<ByteString>>, list: &'de NonEmpty<Value>) -> Self {
+        Self {
+            name,
+            iter: Some(&list.head).into_iter().chain(&list.tail).enumerate(),
+        }
+    }
+    fn single(value: &'de Value) -> Self {
+        Self {
+            name: None,
+            iter: Some(value).into_iter().chain(&[]).enumerate(),
+        }
+    }
+    fn empty() -> Self {
+        Self {
+            name: None,
+            iter: None.into_iter().chain(&[]).enumerate(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let Some((index, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+
+        seed.deserialize(value)
+            .map(Some)
+            .map_err(|e| match self.name {
+                Some(name) => e.within(name.clone(), Some(index)),
+                None => e,
+            })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter.size_hint().1
+    }
+}
+
+/// Deserializes repeated child elements with the same name.
+struct ListDeserializer<'de> {
+    name: &'de ElementName<ByteString>,
+    list: &'de NonEmpty<Value>,
+}
+
+impl<'de> Deserializer<'de> for ListDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqDeserializer::new(Some(self.name), self.list))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+/// The child elements in a list of nodes.
+fn elements(nodes: &[Node]) -> impl Iterator<Item = (&ElementName<ByteString>, &Value)> {
+    nodes.iter().filter_map(|node| match node {
+        Node::Element(name, value) => Some((name, value)),
+        Node::Text(_) => None,
+    })
+}
+
+/// Deserializes the child elements of mixed content whose text is only
+/// whitespace as a struct or map, like [`ValueMap`].
+struct NodesDeserializer<'de> {
+    nodes: &'de [Node],
+    fields: &'static [&'static str],
+    /// The index of the next node
+    index: usize,
+    /// The name of the current entry and the index of its first element
+    entry: Option<(&'de ElementName<ByteString>, usize)>,
+}
+
+impl<'de> NodesDeserializer<'de> {
+    fn new(nodes: &'de [Node], fields: &'static [&'static str]) -> Self {
+        Self {
+            nodes,
+            fields,
+            index: 0,
+            entry: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for NodesDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        while let Some(node) = self.nodes.get(self.index) {
+            let index = self.index;
+            self.index += 1;
+
+            let Node::Element(name, _) = node else {
+                continue;
+            };
+            // repeated elements are part of the entry of the first one
+            if elements(&self.nodes[..index]).any(|(previous, _)| previous == name) {
+                continue;
+            }
+            self.entry = Some((name, index));
+
+            return deserialize_name(seed, name, self.fields).map(Some);
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        let (name, index) = self
+            .entry
+            .take()
+            .expect("`next_value_seed` is called after `next_key_seed`");
+        let nodes = &self.nodes[index..];
+
+        let mut values = elements(nodes).filter(|(other, _)| *other == name);
+        match (values.next(), values.next()) {
+            (Some((_, value)), None) => deserialize_child(seed, name, value),
+            _ => seed.deserialize(NodeListDeserializer { name, nodes }),
+        }
+    }
+}
+
+/// Deserializes repeated child elements with the same name in a list of
+/// nodes.
+struct NodeListDeserializer<'de> {
+    name: &'de ElementName<ByteString>,
+    nodes: &'de [Node],
+}
+
+impl<'de> Deserializer<'de> for NodeListDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(NodeSeqDeserializer {
+            name: self.name,
+            nodes: self.nodes.iter(),
+            index: 0,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+struct NodeSeqDeserializer<'de> {
+    name: &'de ElementName<ByteString>,
+    nodes: std::slice::Iter<'de, Node>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for NodeSeqDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let name = self.name;
+        let Some(value) = self.nodes.find_map(|node| match node {
+            Node::Element(other, value) if other == name => Some(value),
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+
+        seed.deserialize(value)
+            .map(Some)
+            .map_err(|e| e.within(name.clone(), Some(index)))
+    }
+}
+
+/// Deserializes a child element as an enum variant.
+struct EnumDeserializer<'de> {
+    name: &'de ElementName<ByteString>,
+    value: &'de Value,
+    variants: &'static [&'static str],
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = ExtractElementError;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), Self::Error> {
+        let variant = deserialize_name(seed, self.name, self.variants)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = ExtractElementError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        deserialize_child(seed, self.name, self.value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::List(list) => visitor.visit_seq(SeqDeserializer::new(Some(self.name), list)),
+            value => value
+                .deserialize_seq(visitor)
+                .map_err(|e| e.within(self.name.clone(), None)),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value
+            .deserialize_struct("", fields, visitor)
+            .map_err(|e| e.within(self.name.clone(), None))
+    }
+}
@@ -0,0 +1,200 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Conversion between [`Value`]s and types implementing [`serde::Serialize`]
+//! or [`serde::Deserialize`].
+//!
+//! Elements are named using the Clark notation, e.g. `{DAV:}displayname`,
+//! so struct fields can refer to elements in a specific namespace using
+//! `#[serde(rename = "{http://owncloud.org/ns}fileid")]`. When
+//! deserializing, fields without a namespace match elements with that
+//! local name in any namespace.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use webdav_xml::{elements::Properties, FromXml};
+//!
+//! #[derive(Deserialize)]
+//! struct Props {
+//!     displayname: String,
+//!     #[serde(rename = "{http://owncloud.org/ns}fileid")]
+//!     fileid: u64,
+//! }
+//!
+//! let prop = Properties::from_xml(
+//!     r#"<d:prop xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+//!         <d:displayname>example.txt</d:displayname>
+//!         <oc:fileid>42</oc:fileid>
+//!     </d:prop>"#,
+//! )?;
+//! let props: Props = prop.deserialize()?;
+//! assert_eq!(props.displayname, "example.txt");
+//! assert_eq!(props.fileid, 42);
+//! # Ok::<(), webdav_xml::Error>(())
+//! ```
+
+mod de;
+mod ser;
+
+use crate::{ExtractElementError, Value};
+
+pub use self::ser::{MapSerializer, SeqSerializer, SerializeError, Serializer, VariantSerializer};
+
+/// Deserializes an instance of `T` from the content of an element.
+///
+/// See the [`Deserializer`](serde::Deserializer) implementation of
+/// [`&Value`](Value) for how values are mapped.
+pub fn from_value<'de, T>(value: &'de Value) -> Result<T, ExtractElementError>
+where
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(value)
+}
+
+/// Serializes `value` as the content of an element.
+///
+/// Elements without a namespace are not in any namespace, use
+/// [`Serializer::namespace()`] to put them into e.g. `DAV:` instead.
+pub fn to_value<T>(value: &T) -> Result<Value, SerializeError>
+where
+    T: serde::Serialize + ?Sized,
+{
+    value.serialize(Serializer::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{elements::Properties, FromXml, IntoXml, WriterConfig};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Props {
+        displayname: String,
+        #[serde(rename = "{http://owncloud.org/ns}size")]
+        size: u64,
+        #[serde(rename = "{http://owncloud.org/ns}favorite")]
+        favorite: bool,
+        #[serde(rename = "{http://owncloud.org/ns}share-types")]
+        share_types: ShareTypes,
+        getcontenttype: Option<String>,
+        resourcetype: Option<ResourceType>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct ShareTypes {
+        #[serde(rename = "{http://owncloud.org/ns}share-type", default)]
+        share_type: Vec<u32>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum ResourceType {
+        Collection,
+    }
+
+    const XML: &str = r#"
+<d:prop xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <d:displayname>Documents</d:displayname>
+  <oc:size>1234</oc:size>
+  <oc:favorite>true</oc:favorite>
+  <oc:share-types>
+    <oc:share-type>0</oc:share-type>
+    <oc:share-type>3</oc:share-type>
+  </oc:share-types>
+  <d:resourcetype>
+    <d:collection/>
+  </d:resourcetype>
+</d:prop>"#;
+
+    fn props() -> Props {
+        Props {
+            displayname: "Documents".into(),
+            size: 1234,
+            favorite: true,
+            share_types: ShareTypes {
+                share_type: vec![0, 3],
+            },
+            getcontenttype: None,
+            resourcetype: Some(ResourceType::Collection),
+        }
+    }
+
+    #[test]
+    fn deserialize() -> eyre::Result<()> {
+        let prop = Properties::from_xml(XML)?;
+        assert_eq!(prop.deserialize::<Props>()?, props());
+
+        let value = Value::from_xml(XML)?;
+        let map = value.to_map()?.map.first().unwrap().1;
+        assert_eq!(from_value::<Props>(map)?, props());
+
+        // other representations of `true` and empty elements for `None`
+        let xml = XML
+            .replace("true", "1")
+            .replace("</d:displayname>", "</d:displayname><d:getcontenttype/>");
+        assert_eq!(Properties::from_xml(xml)?.deserialize::<Props>()?, props());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_error() -> eyre::Result<()> {
+        let xml = XML.replace("<oc:share-type>3", "<oc:share-type>three");
+        let e = Properties::from_xml(xml)?
+            .deserialize::<Props>()
+            .unwrap_err();
+        assert_eq!(e.path().to_string(), "share-types/share-type[1]");
+
+        let xml = XML.replace("<oc:size>1234</oc:size>", "");
+        let e = Properties::from_xml(xml)?
+            .deserialize::<Props>()
+            .unwrap_err();
+        assert!(matches!(
+            e.kind,
+            crate::ExtractElementErrorKind::MissingElement("{http://owncloud.org/ns}size")
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn serialize() -> eyre::Result<()> {
+        let value = props().serialize(Serializer::new().namespace("DAV:"))?;
+        let prop = Properties::try_from(&value)?;
+
+        assert_eq!(prop.deserialize::<Props>()?, props());
+
+        let config = WriterConfig::new()
+            .declaration(false)
+            .prefix("http://owncloud.org/ns", "oc");
+        let xml = prop.into_xml_with_config(&config)?;
+        assert_str_eq!(std::str::from_utf8(&xml)?, XML.trim());
+
+        Ok(())
+    }
+
+    #[test]
+    fn enums() -> eyre::Result<()> {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        enum Shape {
+            Point,
+            Circle(u32),
+            Rect { w: u32, h: u32 },
+        }
+
+        for shape in [Shape::Point, Shape::Circle(3), Shape::Rect { w: 1, h: 2 }] {
+            assert_eq!(from_value::<Shape>(&to_value(&shape)?)?, shape);
+        }
+
+        let value = Value::from_xml("<shape><Point/></shape>")?;
+        let value = value.to_map()?.map.first().unwrap().1;
+        assert_eq!(from_value::<Shape>(value)?, Shape::Point);
+        assert_eq!(&to_value(&Shape::Point)?, value);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,560 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fmt::Display;
+
+use bytestring::ByteString;
+use nonempty::NonEmpty;
+use serde::ser::{
+    Error as _, Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+
+use crate::{ElementName, Value, ValueMap, DAV_NAMESPACE, DAV_PREFIX};
+
+/// Returned when serializing a value using [`Serializer`] failed.
+#[derive(Debug)]
+pub struct SerializeError(String);
+
+impl std::fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+impl serde::ser::Error for SerializeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A [`serde::Serializer`] that produces a [`Value`].
+///
+/// - primitive types like numbers and strings are text
+/// - structs and maps are child elements
+/// - sequences are repeated child elements
+/// - `()` and empty strings are empty elements
+/// - `None` is an empty element, except for struct fields, which are
+///   skipped
+/// - enum variants are a child element, which is empty for unit variants
+///
+/// Field names and map keys can use the Clark notation to specify a
+/// namespace, e.g. `{DAV:}displayname`. Names without a namespace use the
+/// namespace configured with [`Serializer::namespace()`], if any.
+#[derive(Clone, Debug, Default)]
+pub struct Serializer {
+    namespace: Option<ByteString>,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the namespace of elements whose names don't specify one, e.g.
+    /// `DAV:`.
+    pub fn namespace(mut self, namespace: impl Into<ByteString>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+    fn name(&self, name: &str) -> ElementName<ByteString> {
+        let (namespace, local_name) =
+            match name.strip_prefix('{').and_then(|name| name.split_once('}')) {
+                Some(("", local_name)) => (None, local_name),
+                Some((namespace, local_name)) => (Some(namespace.into()), local_name),
+                None => (self.namespace.clone(), name),
+            };
+        let prefix = match namespace.as_deref() {
+            Some(DAV_NAMESPACE) => Some(DAV_PREFIX.into()),
+            _ => None,
+        };
+
+        ElementName {
+            namespace,
+            prefix,
+            local_name: local_name.into(),
+        }
+    }
+    fn text(self, text: impl Display) -> Result<Value, SerializeError> {
+        Ok(crate::__private::text(text))
+    }
+}
+
+impl serde::Serializer for Serializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_char(self, v: char) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_str(self, v: &str) -> Result<Value, Self::Error> {
+        self.text(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Self::Error> {
+        match std::str::from_utf8(v) {
+            Ok(v) => self.text(v),
+            Err(_) => Err(Self::Error::custom("bytes must be valid UTF-8")),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::Empty)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::Empty)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::Empty)
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        let mut map = ValueMap::new();
+        map.insert_raw(self.name(variant), Value::Empty);
+        Ok(Value::Map(map))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        let mut map = ValueMap::new();
+        map.insert_raw(self.name(variant), value.serialize(self)?);
+        Ok(Value::Map(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqSerializer {
+            serializer: self,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(VariantSerializer {
+            name: self.name(variant),
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapSerializer {
+            serializer: self,
+            map: ValueMap::new(),
+            key: None,
+        })
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(VariantSerializer {
+            name: self.name(variant),
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+/// Serializes a sequence as repeated elements.
+pub struct SeqSerializer {
+    serializer: Serializer,
+    items: Vec<Value>,
+}
+
+impl SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        match value.serialize(self.serializer.clone())? {
+            Value::List(_) => Err(Self::Error::custom("nested sequences are not supported")),
+            item => {
+                self.items.push(item);
+                Ok(())
+            }
+        }
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        let mut items = self.items.into_iter();
+        Ok(match (items.next(), items.len()) {
+            (None, _) => Value::Empty,
+            (Some(item), 0) => item,
+            (Some(head), _) => Value::List(Box::new(NonEmpty {
+                head,
+                tail: items.collect(),
+            })),
+        })
+    }
+}
+
+impl SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// Serializes a map or struct as child elements.
+pub struct MapSerializer {
+    serializer: Serializer,
+    map: ValueMap,
+    key: Option<ElementName<ByteString>>,
+}
+
+impl MapSerializer {
+    fn insert<T: Serialize + ?Sized>(
+        &mut self,
+        name: ElementName<ByteString>,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        let value = value.serialize(self.serializer.clone())?;
+        self.map.insert_raw(name, value);
+        Ok(())
+    }
+}
+
+impl SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        match key.serialize(self.serializer.clone())? {
+            Value::Text(key) => {
+                self.key = Some(self.serializer.name(&key));
+                Ok(())
+            }
+            _ => Err(Self::Error::custom("map keys must be non-empty strings")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .key
+            .take()
+            .expect("`serialize_value` is called after `serialize_key`");
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(crate::__private::from_map(self.map))
+    }
+}
+
+impl SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if value.serialize(IsNone).is_ok() {
+            return Ok(());
+        }
+        self.insert(self.serializer.name(key), value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        SerializeMap::end(self)
+    }
+}
+
+/// Serializes a tuple or struct variant as a child element.
+pub struct VariantSerializer<S> {
+    name: ElementName<ByteString>,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(name: ElementName<ByteString>, value: Value) -> Value {
+        let mut map = ValueMap::new();
+        map.insert_raw(name, value);
+        Value::Map(map)
+    }
+}
+
+impl SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Self::wrap(self.name, SerializeSeq::end(self.inner)?))
+    }
+}
+
+impl SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = Value;
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Self::wrap(self.name, SerializeMap::end(self.inner)?))
+    }
+}
+
+/// Checks whether a value is `None`, without serializing its content.
+///
+/// Serializing succeeds only for `None`, everything else fails at the first
+/// call.
+struct IsNone;
+
+impl serde::Serializer for IsNone {
+    type Ok = ();
+    type Error = SerializeError;
+
+    type SerializeSeq = Impossible<(), SerializeError>;
+    type SerializeTuple = Impossible<(), SerializeError>;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = Impossible<(), SerializeError>;
+    type SerializeStruct = Impossible<(), SerializeError>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_i8(self, _: i8) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_i16(self, _: i16) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_i32(self, _: i32) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_i64(self, _: i64) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_u8(self, _: u8) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_u16(self, _: u16) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_u32(self, _: u32) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_u64(self, _: u64) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_f32(self, _: f32) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_f64(self, _: f64) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_char(self, _: char) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_str(self, _: &str) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(not_none())
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(not_none())
+    }
+}
+
+fn not_none() -> SerializeError {
+    SerializeError(String::new())
+}
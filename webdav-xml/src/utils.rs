@@ -84,3 +84,20 @@ pub(crate) fn test_deserialize<E: crate::FromXml + PartialEq + std::fmt::Debug>(
 
     Ok(())
 }
+
+/// Implements `TryFrom<Value>` by delegating to the `TryFrom<&Value>`
+/// implementation, for elements that don't benefit from taking ownership.
+macro_rules! owned_try_from {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl TryFrom<$crate::Value> for $ty {
+                type Error = $crate::ExtractElementError;
+
+                fn try_from(value: $crate::Value) -> Result<Self, Self::Error> {
+                    Self::try_from(&value)
+                }
+            }
+        )+
+    };
+}
+pub(crate) use owned_try_from;
@@ -29,12 +29,27 @@ pub enum Value {
     /// The parent element contains multiple elements of this type, e.g. `<foo
     /// /><foo />`
     List(Box<NonEmpty<Value>>),
+    /// The element contains text interleaved with other elements, e.g.
+    /// `<foo>bar <baz /></foo>`
+    ///
+    /// The nodes are kept in document order, including whitespace between
+    /// them, so that the content can be serialized exactly as it was parsed.
+    Mixed(Vec<Node>),
+    /// The element has attributes, e.g. `<foo bar="baz" />`
+    ///
+    /// The accessor methods like [`Value::to_text()`] look through this
+    /// variant, so elements that don't care about attributes don't have to
+    /// handle it.
+    WithAttributes {
+        attributes: Attributes,
+        value: Box<Value>,
+    },
 }
 
 impl Value {
     #[track_caller]
     pub fn to_text(&self) -> Result<&ByteString, ExtractElementError> {
-        match self {
+        match self.without_attributes() {
             Self::Text(s) => Ok(s),
             _ => Err(ExtractElementError::new(
                 ExtractElementErrorKind::InvalidValueType {
@@ -47,8 +62,24 @@ impl Value {
 
     #[track_caller]
     pub fn to_map(&self) -> Result<&ValueMap, ExtractElementError> {
+        match self.without_attributes() {
+            Self::Map(map) => Ok(map),
+            _ => Err(ExtractElementError::new(
+                ExtractElementErrorKind::InvalidValueType {
+                    expected: "map",
+                    got: self.value_type(),
+                },
+            )),
+        }
+    }
+
+    /// Like [`Value::to_map()`], but takes ownership of the map instead of
+    /// borrowing it.
+    #[track_caller]
+    pub fn into_map(self) -> Result<ValueMap, ExtractElementError> {
         match self {
             Self::Map(map) => Ok(map),
+            Self::WithAttributes { value, .. } if value.is_map() => value.into_map(),
             _ => Err(ExtractElementError::new(
                 ExtractElementErrorKind::InvalidValueType {
                     expected: "map",
@@ -72,27 +103,103 @@ impl Value {
     }
 
     pub fn is_empty(&self) -> bool {
-        matches!(self, Self::Empty)
+        matches!(self.without_attributes(), Self::Empty)
     }
 
     pub fn is_text(&self) -> bool {
-        matches!(self, Self::Text(_))
+        matches!(self.without_attributes(), Self::Text(_))
     }
 
     pub fn is_map(&self) -> bool {
-        matches!(self, Self::Map(_))
+        matches!(self.without_attributes(), Self::Map(_))
     }
 
     pub fn is_list(&self) -> bool {
         matches!(self, Self::List(_))
     }
 
+    /// Returns the attributes of this element, if there are any.
+    pub fn attributes(&self) -> Option<&Attributes> {
+        match self {
+            Self::WithAttributes { attributes, .. } => Some(attributes),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an attribute of this element.
+    ///
+    /// Attributes without a prefix don't belong to any namespace, so
+    /// `namespace` should be `None` for them.
+    pub fn attribute(&self, namespace: Option<&str>, local_name: &str) -> Option<&ByteString> {
+        self.attributes()?.get(namespace, local_name)
+    }
+
+    /// Returns the content of this element without its attributes.
+    pub fn without_attributes(&self) -> &Value {
+        match self {
+            Self::WithAttributes { value, .. } => value,
+            value => value,
+        }
+    }
+
+    /// The child elements and text of mixed content whose text is only
+    /// whitespace, e.g. a dead property that is formatted across multiple
+    /// lines.
+    pub(crate) fn formatted_elements(&self) -> Option<&[Node]> {
+        match self.without_attributes() {
+            Self::Mixed(nodes)
+                if nodes
+                    .iter()
+                    .all(|node| !matches!(node, Node::Text(text) if !text.trim().is_empty())) =>
+            {
+                Some(nodes)
+            }
+            _ => None,
+        }
+    }
+
+    /// Add an attribute to this element.
+    ///
+    /// If this value is a [`Value::List`], which represents multiple
+    /// elements, the attribute is added to each of them.
+    pub fn with_attribute(
+        self,
+        name: ElementName<ByteString>,
+        attribute_value: impl Into<ByteString>,
+    ) -> Value {
+        let attribute_value = attribute_value.into();
+        match self {
+            Self::List(list) => {
+                Self::List(Box::new(list.map(|value| {
+                    value.with_attribute(name.clone(), attribute_value.clone())
+                })))
+            }
+            Self::WithAttributes {
+                mut attributes,
+                value,
+            } => {
+                attributes.insert(name, attribute_value);
+                Self::WithAttributes { attributes, value }
+            }
+            value => {
+                let mut attributes = Attributes::new();
+                attributes.insert(name, attribute_value);
+                Self::WithAttributes {
+                    attributes,
+                    value: Box::new(value),
+                }
+            }
+        }
+    }
+
     fn value_type(&self) -> &'static str {
         match self {
             Value::Empty => "empty",
             Value::Text(..) => "text",
             Value::Map(..) => "map",
             Value::List(..) => "list",
+            Value::Mixed(..) => "mixed",
+            Value::WithAttributes { value, .. } => value.value_type(),
         }
     }
 }
@@ -103,15 +210,75 @@ impl From<String> for Value {
     }
 }
 
+/// A node inside [`Value::Mixed`] content.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    /// A text node
+    Text(ByteString),
+    /// A child element
+    Element(ElementName<ByteString>, Value),
+}
+
+/// The attributes of an XML element, excluding namespace declarations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Attributes(IndexMap<ElementName<ByteString>, ByteString>);
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the value of an attribute.
+    pub fn get(&self, namespace: Option<&str>, local_name: &str) -> Option<&ByteString> {
+        self.0.get(&ElementName {
+            namespace,
+            prefix: None,
+            local_name,
+        })
+    }
+    /// Insert an attribute, replacing any existing attribute with the same
+    /// name.
+    pub fn insert(&mut self, name: ElementName<ByteString>, value: ByteString) {
+        self.0.insert(name, value);
+    }
+    /// Iterate over the attributes in document order.
+    pub fn iter(&self) -> impl Iterator<Item = (&ElementName<ByteString>, &ByteString)> {
+        self.0.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<(ElementName<ByteString>, ByteString)> for Attributes {
+    fn from_iter<T: IntoIterator<Item = (ElementName<ByteString>, ByteString)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
 pub(crate) type InnerValueMap = IndexMap<ElementName<ByteString>, Value>;
 
 /// A mapping from tag names to [`Value`]s.
-#[derive(Clone, Debug, Default, PartialEq)]
-pub struct ValueMap(pub(crate) InnerValueMap);
+///
+/// Child elements are grouped by their name, but the order in which they were
+/// inserted is remembered and can be retrieved using [`ValueMap::iter()`].
+/// Comparing two maps with `==` ignores this order.
+#[derive(Clone, Debug, Default)]
+pub struct ValueMap {
+    pub(crate) map: InnerValueMap,
+    /// Indices into `map`, one for each inserted child element, in insertion
+    /// order.
+    ///
+    /// This is empty as long as the insertion order is the same as the order
+    /// of `map`, which is the case for most elements.
+    order: Vec<usize>,
+}
 
 impl ValueMap {
     pub fn new() -> Self {
-        Self(IndexMap::new())
+        Self::default()
     }
     /// Extract a child element of a specific type.
     ///
@@ -124,9 +291,9 @@ impl ValueMap {
     where
         E: Element + TryFrom<&'v Value, Error = ExtractElementError>,
     {
-        self.0
+        self.map
             .get(&E::element_name::<&'static str>())
-            .map(E::try_from)
+            .map(|value| E::try_from(value).map_err(|e| e.within(E::element_name(), None)))
     }
     /// Extract a non-empty child element of a specific type.
     ///
@@ -142,11 +309,11 @@ impl ValueMap {
     where
         E: Element + TryFrom<&'v Value, Error = ExtractElementError>,
     {
-        self.0
+        self.map
             .get(&E::element_name::<&'static str>())
-            .map(|value| match value {
-                Value::Empty => None,
-                v => Some(v.try_into()),
+            .map(|value| match value.is_empty() {
+                true => None,
+                false => Some(E::try_from(value).map_err(|e| e.within(E::element_name(), None))),
             })
     }
     /// Insert a child value into the map.
@@ -154,6 +321,55 @@ impl ValueMap {
         let key = E::element_name();
         self.insert_raw(key, value)
     }
+    /// Iterate over all child elements in the order in which they were
+    /// inserted, i.e. in document order for parsed XML.
+    ///
+    /// Unlike iterating over [`ValueMap::as_ref()`], multiple elements with
+    /// the same name are yielded individually instead of as a [`Value::List`].
+    pub fn iter(&self) -> impl Iterator<Item = (&ElementName<ByteString>, &Value)> {
+        enum Iter<O, G> {
+            Ordered(O),
+            Grouped(G),
+        }
+
+        impl<T, O: Iterator<Item = T>, G: Iterator<Item = T>> Iterator for Iter<O, G> {
+            type Item = T;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                match self {
+                    Self::Ordered(inner) => inner.next(),
+                    Self::Grouped(inner) => inner.next(),
+                }
+            }
+        }
+
+        fn items(value: &Value) -> impl Iterator<Item = &Value> {
+            match value {
+                Value::List(list) => std::slice::from_ref(&list.head).iter().chain(&list.tail),
+                value => std::slice::from_ref(value).iter().chain(&[]),
+            }
+        }
+
+        if self.order.is_empty() {
+            return Iter::Grouped(
+                self.map
+                    .iter()
+                    .flat_map(|(name, value)| items(value).map(move |item| (name, item))),
+            );
+        }
+
+        let mut consumed = vec![0; self.map.len()];
+        Iter::Ordered(self.order.iter().filter_map(move |&index| {
+            let (name, value) = self.map.get_index(index)?;
+            let item = match value {
+                Value::List(list) => list.get(consumed[index])?,
+                value if consumed[index] == 0 => value,
+                _ => return None,
+            };
+            consumed[index] += 1;
+            Some((name, item))
+        }))
+    }
 }
 
 impl ValueMap {
@@ -181,12 +397,56 @@ impl ValueMap {
             }
         }
 
-        match self.0.get(&E::element_name::<&'static str>()) {
+        match self.map.get(&E::element_name::<&'static str>()) {
             Some(Value::List(list)) => ElementIter::List(list.iter()),
             Some(value) => ElementIter::Single(std::iter::once(value)),
             None => ElementIter::Empty,
         }
-        .map(E::try_from)
+        .enumerate()
+        .map(|(index, value)| {
+            E::try_from(value).map_err(|e| e.within(E::element_name(), Some(index)))
+        })
+    }
+    /// Like [`ValueMap::get()`], but removes the child element from the map
+    /// instead of cloning it.
+    pub(crate) fn take<E>(&mut self) -> Option<Result<E, ExtractElementError>>
+    where
+        E: Element + TryFrom<Value, Error = ExtractElementError>,
+    {
+        self.remove(&E::element_name::<&'static str>())
+            .map(|value| E::try_from(value).map_err(|e| e.within(E::element_name(), None)))
+    }
+    /// Like [`ValueMap::iter_all()`], but removes the child elements from the
+    /// map instead of cloning them.
+    pub(crate) fn take_all<E>(&mut self) -> impl Iterator<Item = Result<E, ExtractElementError>>
+    where
+        E: Element + TryFrom<Value, Error = ExtractElementError>,
+    {
+        self.remove(&E::element_name::<&'static str>())
+            .map(|value| match value {
+                Value::List(list) => *list,
+                value => NonEmpty::new(value),
+            })
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, value)| {
+                E::try_from(value).map_err(|e| e.within(E::element_name(), Some(index)))
+            })
+    }
+    fn remove(&mut self, key: &ElementName<&str>) -> Option<Value> {
+        let (index, _, value) = self.map.shift_remove_full(key)?;
+
+        if !self.order.is_empty() {
+            self.order.retain(|&i| i != index);
+            for i in &mut self.order {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
+        }
+
+        Some(value)
     }
     // pub(crate) fn iter_all_nonempty<'v, E>(&'v self) -> impl Iterator<Item =
     // Result<E, Error>> + 'v where
@@ -216,32 +476,141 @@ impl ValueMap {
     //     .ok_or(Error::MissingElement(E::LOCAL_NAME))?
     // }
     pub(crate) fn insert_raw(&mut self, key: ElementName<ByteString>, value: Value) {
-        match self.0.get_mut(&key) {
-            Some(Value::List(list)) => list.push(value),
-            Some(old_value) => {
-                *old_value = Value::List(Box::new(nonempty![std::mem::take(old_value), value]));
+        let count = match &value {
+            Value::List(list) => list.len(),
+            _ => 1,
+        };
+
+        if self.order.is_empty()
+            && self
+                .map
+                .get_index_of(&key)
+                .is_some_and(|index| index + 1 != self.map.len())
+        {
+            self.order = map_order(&self.map);
+        }
+
+        let index = match self.map.get_full_mut(&key) {
+            Some((index, _, Value::List(list))) => {
+                list.push(value);
+                index
             }
-            None => {
-                self.0.insert(key, value);
+            Some((index, _, old_value)) => {
+                *old_value = Value::List(Box::new(nonempty![std::mem::take(old_value), value]));
+                index
             }
+            None => self.map.insert_full(key, value).0,
+        };
+
+        if !self.order.is_empty() {
+            self.order.extend(std::iter::repeat_n(index, count));
         }
     }
 }
 
+impl PartialEq for ValueMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
 impl AsRef<InnerValueMap> for ValueMap {
     fn as_ref(&self) -> &InnerValueMap {
-        &self.0
+        &self.map
     }
 }
 
+/// The map can be changed arbitrarily, so [`ValueMap::iter()`] forgets the
+/// insertion order and uses the order of the map afterwards.
 impl AsMut<InnerValueMap> for ValueMap {
     fn as_mut(&mut self) -> &mut InnerValueMap {
-        &mut self.0
+        self.order.clear();
+        &mut self.map
     }
 }
 
 impl From<InnerValueMap> for ValueMap {
     fn from(map: InnerValueMap) -> Self {
-        Self(map)
+        Self {
+            map,
+            order: Vec::new(),
+        }
+    }
+}
+
+/// The indices of the child elements of `map` if they were inserted in the
+/// order of `map`.
+fn map_order(map: &InnerValueMap) -> Vec<usize> {
+    map.values()
+        .enumerate()
+        .flat_map(|(index, value)| {
+            let count = match value {
+                Value::List(list) => list.len(),
+                _ => 1,
+            };
+            std::iter::repeat_n(index, count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn name(local_name: &str) -> ElementName<ByteString> {
+        ElementName {
+            namespace: None,
+            prefix: None,
+            local_name: local_name.into(),
+        }
+    }
+
+    fn names(map: &ValueMap) -> Vec<&str> {
+        map.iter()
+            .map(|(name, _)| name.local_name.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn iter_order() {
+        let mut map = ValueMap::new();
+        map.insert_raw(name("a"), Value::Empty);
+        map.insert_raw(name("a"), Value::Empty);
+        map.insert_raw(name("b"), Value::Empty);
+        assert_eq!(names(&map), ["a", "a", "b"]);
+
+        map.insert_raw(name("a"), Value::Empty);
+        map.insert_raw(name("c"), Value::Empty);
+        assert_eq!(names(&map), ["a", "a", "b", "a", "c"]);
+
+        // the order of the map is used after modifying it directly
+        map.as_mut().shift_remove(&name("a"));
+        map.insert_raw(name("b"), Value::Empty);
+        assert_eq!(names(&map), ["b", "c", "b"]);
+    }
+
+    #[test]
+    fn with_attribute_list() {
+        let list = Value::List(Box::new(nonempty::nonempty![
+            Value::Text("a".into()),
+            Value::Empty.with_attribute(name("id"), "1"),
+        ]));
+
+        let value = list.with_attribute(name("lang"), "en");
+        assert_eq!(
+            value,
+            Value::List(Box::new(nonempty::nonempty![
+                Value::Text("a".into()).with_attribute(name("lang"), "en"),
+                Value::Empty
+                    .with_attribute(name("id"), "1")
+                    .with_attribute(name("lang"), "en"),
+            ]))
+        );
+
+        let mut items = value.to_list().unwrap().iter();
+        assert_eq!(items.next().unwrap().attributes().unwrap().len(), 1);
+        assert_eq!(items.next().unwrap().attributes().unwrap().len(), 2);
     }
 }
@@ -2,30 +2,260 @@
 //
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use std::{borrow::Cow, collections::HashMap};
+use std::borrow::Cow;
 
 use bytestring::ByteString;
+use indexmap::IndexMap;
 
 use crate::{
     element::{Element, ElementExt, ElementName},
+    elements::{Multistatus, Response, ResponseDescription},
+    value::{Attributes, Node},
     Value, XmlError,
 };
 
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Options for serializing XML.
+#[derive(Clone, Debug)]
+pub struct WriterConfig {
+    cdata_threshold: Option<usize>,
+    prefixes: IndexMap<ByteString, ByteString>,
+    indent: Option<usize>,
+    declaration: bool,
+    default_namespace: Option<ByteString>,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        Self {
+            cdata_threshold: None,
+            prefixes: IndexMap::new(),
+            indent: Some(2),
+            declaration: true,
+            default_namespace: None,
+        }
+    }
+}
+
+impl WriterConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Put each element on its own line and indent it by `width` spaces per
+    /// level.
+    ///
+    /// This is the default, with a width of 2.
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+    /// Don't add any whitespace between elements.
+    pub fn compact(mut self) -> Self {
+        self.indent = None;
+        self
+    }
+    /// Whether to write the XML declaration
+    /// (`<?xml version="1.0" encoding="utf-8"?>`).
+    ///
+    /// By default, it is written.
+    pub fn declaration(mut self, declaration: bool) -> Self {
+        self.declaration = declaration;
+        self
+    }
+    /// Declare `namespace` as the default namespace, e.g. `xmlns="DAV:"`, and
+    /// write its elements without a prefix.
+    ///
+    /// Attributes without a prefix never belong to a namespace, so if
+    /// attributes use this namespace, it is also declared with a prefix for
+    /// them.
+    pub fn default_namespace(mut self, namespace: impl Into<ByteString>) -> Self {
+        self.default_namespace = Some(namespace.into());
+        self
+    }
+    /// Write text nodes that contain at least `threshold` markup characters
+    /// (`<`, `>` and `&`) as CDATA sections instead of escaping them.
+    ///
+    /// By default, text is always escaped.
+    pub fn cdata_threshold(mut self, threshold: usize) -> Self {
+        self.cdata_threshold = Some(threshold);
+        self
+    }
+    /// Use `prefix` for `namespace`, e.g. `C` for
+    /// `urn:ietf:params:xml:ns:caldav`.
+    ///
+    /// Otherwise, the [`Element::PREFIX`] of the first element in the
+    /// namespace is used, unless another namespace already uses it. Elements
+    /// without a prefix, e.g. parsed dead properties, get a unique prefix
+    /// like `NS0`.
+    pub fn prefix(
+        mut self,
+        namespace: impl Into<ByteString>,
+        prefix: impl Into<ByteString>,
+    ) -> Self {
+        self.prefixes.insert(namespace.into(), prefix.into());
+        self
+    }
+}
+
 pub(crate) fn write_xml<E: Element>(
     writer: impl std::io::Write,
     value: Value,
+    config: &WriterConfig,
 ) -> Result<(), XmlError> {
-    let mut writer = XmlWriter {
-        inner: quick_xml::Writer::new_with_indent(writer, b' ', 2),
-        namespaces: HashMap::new(),
-    };
-    writer.inner.write_event(quick_xml::events::Event::Decl(
-        quick_xml::events::BytesDecl::new("1.0", Some("utf-8"), None),
-    ))?;
+    let mut writer = XmlWriter::new(writer, config);
+    writer.write_declaration()?;
 
     let name = E::element_name();
     writer.resolve_namespaces(&name, &value);
-    writer.write_toplevel(&name, value)
+    writer.write_toplevel(&name, &value)
+}
+
+/// Writes a `multistatus` element one `response` element at a time.
+///
+/// Unlike [`IntoXml`](crate::IntoXml), this doesn't need all responses in
+/// memory, so it can be used for very large responses, e.g. to a `PROPFIND`
+/// request with `Depth: infinity`.
+///
+/// Each response is serialized into an internal buffer and then written to
+/// the underlying writer. With the `tokio` feature, the `*_async` methods can
+/// be used for writers implementing
+/// [`tokio::io::AsyncWrite`](https://docs.rs/tokio/latest/tokio/io/trait.AsyncWrite.html).
+///
+/// Namespaces that are used by a response, but not declared on the
+/// `multistatus` element, are declared on the `response` element.
+pub struct MultistatusWriter<W> {
+    writer: XmlWriter<Vec<u8>>,
+    sink: W,
+}
+
+impl<W> MultistatusWriter<W> {
+    /// Create a new writer. The XML declaration and the start tag of the
+    /// `multistatus` element are written with the first response.
+    pub fn new(sink: W) -> Self {
+        Self::with_config(sink, &WriterConfig::default())
+    }
+    pub fn with_config(sink: W, config: &WriterConfig) -> Self {
+        use quick_xml::events::Event;
+
+        let mut writer = XmlWriter::new(Vec::new(), config);
+
+        let name = Multistatus::element_name();
+        writer.add_namespace(&name);
+        let namespaces = writer.declarations();
+        let raw_name = writer.name(&name).into_owned();
+        let start = writer.start(&raw_name, &namespaces, None);
+
+        // writing to a `Vec` can't fail
+        let _ = writer.write_declaration();
+        let _ = writer.inner.write_event(Event::Start(start));
+        writer.default_namespace_in_scope = namespaces.iter().any(|(_, prefix)| prefix.is_empty());
+
+        Self { writer, sink }
+    }
+    fn buffer_element<E: Element>(&mut self, value: Value) -> Result<(), XmlError> {
+        let name = E::element_name();
+        let namespaces = self.writer.namespaces.clone();
+        let default_prefix = self.writer.default_prefix.clone();
+        let declared = self.writer.declarations();
+
+        self.writer.resolve_namespaces(&name, &value);
+        let declarations = self
+            .writer
+            .declarations()
+            .into_iter()
+            .filter(|declaration| !declared.contains(declaration))
+            .collect::<Vec<_>>();
+
+        let result = self
+            .writer
+            .write_element(&name, &declarations, None, &value);
+        self.writer.namespaces = namespaces;
+        self.writer.default_prefix = default_prefix;
+        result
+    }
+    fn buffer_end(&mut self) -> Result<(), XmlError> {
+        use quick_xml::events::{BytesEnd, Event};
+
+        let raw_name = self.writer.name(&Multistatus::element_name()).into_owned();
+        self.writer
+            .inner
+            .write_event(Event::End(BytesEnd::new(raw_name)))?;
+
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> MultistatusWriter<W> {
+    /// Write a `response` element.
+    pub fn write_response(&mut self, response: Response) -> Result<(), XmlError> {
+        self.buffer_element::<Response>(response.into())?;
+        self.flush()
+    }
+    /// Write a `responsedescription` element. This should be called after
+    /// all responses were written.
+    pub fn write_responsedescription(
+        &mut self,
+        responsedescription: ResponseDescription,
+    ) -> Result<(), XmlError> {
+        self.buffer_element::<ResponseDescription>(responsedescription.into())?;
+        self.flush()
+    }
+    /// Write the end tag of the `multistatus` element and return the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, XmlError> {
+        self.buffer_end()?;
+        self.flush()?;
+        self.sink.flush().map_err(quick_xml::Error::from)?;
+        Ok(self.sink)
+    }
+    fn flush(&mut self) -> Result<(), XmlError> {
+        let buf = self.writer.inner.get_mut();
+        self.sink.write_all(buf).map_err(quick_xml::Error::from)?;
+        buf.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin> MultistatusWriter<W> {
+    /// Write a `response` element.
+    pub async fn write_response_async(&mut self, response: Response) -> Result<(), XmlError> {
+        self.buffer_element::<Response>(response.into())?;
+        self.flush_async().await
+    }
+    /// Write a `responsedescription` element. This should be called after
+    /// all responses were written.
+    pub async fn write_responsedescription_async(
+        &mut self,
+        responsedescription: ResponseDescription,
+    ) -> Result<(), XmlError> {
+        self.buffer_element::<ResponseDescription>(responsedescription.into())?;
+        self.flush_async().await
+    }
+    /// Write the end tag of the `multistatus` element, flush and return the
+    /// underlying writer.
+    pub async fn finish_async(mut self) -> Result<W, XmlError> {
+        use tokio::io::AsyncWriteExt;
+
+        self.buffer_end()?;
+        self.flush_async().await?;
+        self.sink.flush().await.map_err(quick_xml::Error::from)?;
+        Ok(self.sink)
+    }
+    async fn flush_async(&mut self) -> Result<(), XmlError> {
+        use tokio::io::AsyncWriteExt;
+
+        let buf = self.writer.inner.get_mut();
+        self.sink
+            .write_all(buf)
+            .await
+            .map_err(quick_xml::Error::from)?;
+        buf.clear();
+
+        Ok(())
+    }
 }
 
 struct XmlWriter<W>
@@ -33,7 +263,44 @@ where
     W: std::io::Write,
 {
     inner: quick_xml::Writer<W>,
-    namespaces: HashMap<ByteString, ByteString>,
+    /// The prefixes of the declared namespaces, in the order in which they
+    /// are declared
+    namespaces: IndexMap<ByteString, ByteString>,
+    /// The prefix of the default namespace for attributes, which can't use
+    /// the default namespace
+    default_prefix: Option<ByteString>,
+    /// Whether the configured default namespace is declared on the current
+    /// element or one of its ancestors
+    default_namespace_in_scope: bool,
+    config: WriterConfig,
+}
+
+impl<W> XmlWriter<W>
+where
+    W: std::io::Write,
+{
+    fn new(writer: W, config: &WriterConfig) -> Self {
+        Self {
+            inner: match config.indent {
+                Some(width) => quick_xml::Writer::new_with_indent(writer, b' ', width),
+                None => quick_xml::Writer::new(writer),
+            },
+            namespaces: IndexMap::new(),
+            default_prefix: None,
+            default_namespace_in_scope: false,
+            config: config.clone(),
+        }
+    }
+    fn write_declaration(&mut self) -> Result<(), XmlError> {
+        use quick_xml::events::{BytesDecl, Event};
+
+        if self.config.declaration {
+            self.inner
+                .write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<W> XmlWriter<W>
@@ -41,16 +308,80 @@ where
     W: std::io::Write,
 {
     fn add_namespace(&mut self, name: &ElementName<ByteString>) {
-        if let Some(namespace) = &name.namespace {
-            if !self.namespaces.contains_key(namespace) {
-                self.namespaces.insert(
-                    namespace.clone(),
-                    name.prefix.as_ref().cloned().unwrap_or_else(|| "NS".into()),
-                );
-                // TODO: handle collisions
-            }
+        let Some(namespace) = &name.namespace else {
+            return;
+        };
+        if namespace == XML_NAMESPACE || self.namespaces.contains_key(namespace) {
+            return;
+        }
+        if self.config.default_namespace.as_ref() == Some(namespace) {
+            self.namespaces.insert(namespace.clone(), ByteString::new());
+            return;
+        }
+
+        let prefix = self.available_prefix(namespace, name.prefix.as_ref());
+        self.namespaces.insert(namespace.clone(), prefix);
+    }
+    /// Like [`XmlWriter::add_namespace()`], but attributes without a prefix
+    /// never belong to a namespace, so an attribute in the default namespace
+    /// needs another prefix.
+    fn add_attribute_namespace(&mut self, name: &ElementName<ByteString>) {
+        self.add_namespace(name);
+
+        let Some(namespace) = &name.namespace else {
+            return;
+        };
+        if self.default_prefix.is_none()
+            && self
+                .namespaces
+                .get(namespace)
+                .is_some_and(|prefix| prefix.is_empty())
+        {
+            self.default_prefix = Some(self.available_prefix(namespace, name.prefix.as_ref()));
         }
     }
+    fn available_prefix(
+        &self,
+        namespace: &ByteString,
+        preferred: Option<&ByteString>,
+    ) -> ByteString {
+        let is_available = |prefix: &str| {
+            !prefix.is_empty()
+                // prefixes starting with `xml` are reserved
+                && !prefix.to_ascii_lowercase().starts_with("xml")
+                && !self.namespaces.values().any(|used| used == prefix)
+                && self.default_prefix.as_deref() != Some(prefix)
+                && !self
+                    .config
+                    .prefixes
+                    .iter()
+                    .any(|(other, preferred)| preferred == prefix && other != namespace)
+        };
+        [self.config.prefixes.get(namespace), preferred]
+            .into_iter()
+            .flatten()
+            .find(|prefix| is_available(prefix))
+            .cloned()
+            .unwrap_or_else(|| {
+                (0..)
+                    .map(|i| ByteString::from(format!("NS{i}")))
+                    .find(|prefix| is_available(prefix))
+                    .expect("there are infinitely many prefixes")
+            })
+    }
+    /// The namespace declarations for the resolved namespaces.
+    fn declarations(&self) -> Vec<(ByteString, ByteString)> {
+        let default = self.default_prefix.as_ref().and_then(|prefix| {
+            let namespace = self.config.default_namespace.as_ref()?;
+            Some((namespace.clone(), prefix.clone()))
+        });
+
+        self.namespaces
+            .iter()
+            .map(|(namespace, prefix)| (namespace.clone(), prefix.clone()))
+            .chain(default)
+            .collect()
+    }
     fn resolve_namespaces(&mut self, name: &ElementName<ByteString>, value: &Value) {
         match value {
             Value::Text(_) | Value::Empty => self.add_namespace(name),
@@ -61,10 +392,24 @@ where
             }
             Value::Map(map) => {
                 self.add_namespace(name);
-                for (name, value) in &map.0 {
+                for (name, value) in &map.map {
                     self.resolve_namespaces(name, value);
                 }
             }
+            Value::Mixed(nodes) => {
+                self.add_namespace(name);
+                for node in nodes {
+                    if let Node::Element(name, value) = node {
+                        self.resolve_namespaces(name, value);
+                    }
+                }
+            }
+            Value::WithAttributes { attributes, value } => {
+                for (attribute, _) in attributes.iter() {
+                    self.add_attribute_namespace(attribute);
+                }
+                self.resolve_namespaces(name, value);
+            }
         }
     }
 }
@@ -75,109 +420,533 @@ where
 {
     fn name<'n>(&self, name: &'n ElementName<ByteString>) -> Cow<'n, str> {
         match &name.namespace {
-            Some(namespace) => Cow::Owned(format!(
-                "{prefix}:{local_name}",
-                prefix = self
+            Some(namespace) if namespace == XML_NAMESPACE => {
+                Cow::Owned(format!("xml:{local_name}", local_name = name.local_name))
+            }
+            Some(namespace) => {
+                let prefix = self
                     .namespaces
                     .get(namespace)
-                    .expect("all namespaces should be resolved in the first pass"),
-                local_name = name.local_name
-            )),
+                    .expect("all namespaces should be resolved in the first pass");
+                match prefix.is_empty() {
+                    true => Cow::Borrowed(&name.local_name),
+                    false => Cow::Owned(format!("{prefix}:{}", name.local_name)),
+                }
+            }
             None => Cow::Borrowed(&name.local_name),
         }
     }
+    fn attribute_name<'n>(&self, name: &'n ElementName<ByteString>) -> Cow<'n, str> {
+        match (&name.namespace, &self.default_prefix) {
+            (Some(namespace), Some(prefix))
+                if self.config.default_namespace.as_ref() == Some(namespace) =>
+            {
+                Cow::Owned(format!("{prefix}:{}", name.local_name))
+            }
+            _ => self.name(name),
+        }
+    }
+    fn start<'n>(
+        &self,
+        raw_name: &'n str,
+        namespaces: &[(ByteString, ByteString)],
+        attributes: Option<&Attributes>,
+    ) -> quick_xml::events::BytesStart<'n> {
+        use quick_xml::events::{attributes::Attribute, BytesStart};
+
+        let mut start = BytesStart::new(raw_name);
+        for (namespace, prefix) in namespaces {
+            let key = match prefix.is_empty() {
+                true => Cow::Borrowed("xmlns"),
+                false => Cow::Owned(format!("xmlns:{prefix}")),
+            };
+            start.push_attribute(Attribute::from((&*key, &**namespace)));
+        }
+        for (name, value) in attributes.into_iter().flat_map(Attributes::iter) {
+            start.push_attribute(Attribute::from((&*self.attribute_name(name), &**value)));
+        }
+        start
+    }
     fn write_toplevel(
         &mut self,
         name: &ElementName<ByteString>,
-        value: Value,
+        value: &Value,
     ) -> Result<(), XmlError> {
-        use quick_xml::{
-            escape::partial_escape,
-            events::{attributes::Attribute, BytesEnd, BytesStart, BytesText, Event},
-        };
-
-        let raw_name = self.name(name);
+        // all namespaces are declared on the root element, or on each element
+        // if the value is a list, which results in a fragment with multiple
+        // root elements
+        let namespaces = self.declarations();
 
+        self.write_value(name, &namespaces, value)
+    }
+    fn write_value(
+        &mut self,
+        name: &ElementName<ByteString>,
+        namespaces: &[(ByteString, ByteString)],
+        value: &Value,
+    ) -> Result<(), XmlError> {
+        self.write_items(name, namespaces, None, value)
+    }
+    /// Writes an element for each item of a list, with the attributes of all
+    /// surrounding [`Value::WithAttributes`].
+    ///
+    /// If the same attribute is set multiple times, the innermost value is
+    /// used.
+    fn write_items(
+        &mut self,
+        name: &ElementName<ByteString>,
+        namespaces: &[(ByteString, ByteString)],
+        attributes: Option<Cow<'_, Attributes>>,
+        value: &Value,
+    ) -> Result<(), XmlError> {
         match value {
-            Value::Map(map) => {
-                let mut start = BytesStart::new(&*raw_name);
-                for (namespace, prefix) in &self.namespaces {
-                    start.push_attribute(Attribute::from((
-                        &*format!("xmlns:{prefix}"),
-                        &**namespace,
-                    )));
-                }
-
-                self.inner.write_event(Event::Start(start))?;
-                for (tag, value) in map.0 {
-                    self.write_value(&tag, value)?;
+            Value::List(list) => {
+                for value in list.iter() {
+                    self.write_items(name, namespaces, attributes.clone(), value)?;
                 }
-                self.inner
-                    .write_event(Event::End(BytesEnd::new(raw_name)))?;
-
                 Ok(())
             }
-            Value::Text(text) => {
-                let mut start = BytesStart::new(&*raw_name);
-                for (namespace, prefix) in &self.namespaces {
-                    start.push_attribute(Attribute::from((
-                        &*format!("xmlns:{prefix}"),
-                        &**namespace,
-                    )));
-                }
-
-                self.inner.write_event(Event::Start(start))?;
-                self.inner
-                    .write_event(Event::Text(BytesText::from_escaped(partial_escape(&text))))?;
-                self.inner
-                    .write_event(Event::End(BytesEnd::new(raw_name)))?;
-
-                Ok(())
+            Value::WithAttributes {
+                attributes: inner,
+                value,
+            } => {
+                let attributes = match attributes {
+                    None => Cow::Borrowed(inner),
+                    Some(outer) => {
+                        let mut attributes = outer.into_owned();
+                        for (name, value) in inner.iter() {
+                            attributes.insert(name.clone(), value.clone());
+                        }
+                        Cow::Owned(attributes)
+                    }
+                };
+                self.write_items(name, namespaces, Some(attributes), value)
             }
-            _ => unimplemented!(),
+            value => self.write_element(name, namespaces, attributes.as_deref(), value),
         }
     }
-    fn write_value(
+    fn write_element(
         &mut self,
         name: &ElementName<ByteString>,
-        value: Value,
+        namespaces: &[(ByteString, ByteString)],
+        attributes: Option<&Attributes>,
+        value: &Value,
     ) -> Result<(), XmlError> {
-        use quick_xml::{
-            escape::partial_escape,
-            events::{BytesEnd, BytesStart, BytesText, Event},
-        };
+        use quick_xml::events::{BytesEnd, Event};
 
         let raw_name = self.name(name);
+        let mut start = self.start(&raw_name, namespaces, attributes);
+
+        // elements without a namespace can't be written while a default
+        // namespace is in scope, so it is undeclared and redeclared as needed
+        let in_scope = self.default_namespace_in_scope;
+        if namespaces.iter().any(|(_, prefix)| prefix.is_empty()) {
+            self.default_namespace_in_scope = true;
+        } else if name.namespace.is_none() && in_scope {
+            start.push_attribute(("xmlns", ""));
+            self.default_namespace_in_scope = false;
+        } else if let Some(namespace) = name.namespace.as_ref().filter(|namespace| {
+            !in_scope && self.config.default_namespace.as_ref() == Some(namespace)
+        }) {
+            start.push_attribute(("xmlns", &**namespace));
+            self.default_namespace_in_scope = true;
+        }
 
         match value {
             Value::Empty => {
-                self.inner
-                    .write_event(Event::Empty(BytesStart::new(raw_name)))?;
+                self.inner.write_event(Event::Empty(start))?;
             }
             Value::Text(text) => {
+                self.inner.write_event(Event::Start(start))?;
+                self.write_text(text)?;
                 self.inner
-                    .write_event(Event::Start(BytesStart::new(&*raw_name)))?;
-                self.inner
-                    .write_event(Event::Text(BytesText::from_escaped(partial_escape(&text))))?;
-                self.inner
-                    .write_event(Event::End(BytesEnd::new(raw_name)))?;
-            }
-            Value::List(list) => {
-                for value in *list {
-                    self.write_value(name, value)?;
-                }
+                    .write_event(Event::End(BytesEnd::new(&*raw_name)))?;
             }
             Value::Map(map) => {
-                self.inner
-                    .write_event(Event::Start(BytesStart::new(&*raw_name)))?;
-                for (tag, value) in map.0 {
-                    self.write_value(&tag, value)?;
+                self.inner.write_event(Event::Start(start))?;
+                for (tag, value) in map.iter() {
+                    self.write_value(tag, &[], value)?;
                 }
                 self.inner
-                    .write_event(Event::End(BytesEnd::new(raw_name)))?;
+                    .write_event(Event::End(BytesEnd::new(&*raw_name)))?;
+            }
+            Value::Mixed(nodes) => {
+                self.inner.write_event(Event::Start(start))?;
+                self.write_mixed(nodes)?;
+                self.inner
+                    .write_event(Event::End(BytesEnd::new(&*raw_name)))?;
+            }
+            Value::List(_) | Value::WithAttributes { .. } => {
+                unreachable!("lists and attributes are handled by write_items()")
             }
         }
 
+        self.default_namespace_in_scope = in_scope;
+        Ok(())
+    }
+    fn write_text(&mut self, text: &str) -> Result<(), XmlError> {
+        use quick_xml::{
+            escape::partial_escape,
+            events::{BytesCData, BytesText, Event},
+        };
+
+        let use_cdata = self.config.cdata_threshold.is_some_and(|threshold| {
+            text.chars()
+                .filter(|c| matches!(c, '<' | '>' | '&'))
+                .count()
+                >= threshold
+        });
+
+        if !use_cdata {
+            self.inner
+                .write_event(Event::Text(BytesText::from_escaped(partial_escape(text))))?;
+            return Ok(());
+        }
+
+        // a CDATA section can't contain `]]>`, so it is split into two
+        // sections between `]]` and `>`
+        let content = text.replace("]]>", "]]]]><![CDATA[>");
+        self.inner
+            .write_event(Event::CData(BytesCData::new(content)))?;
+
+        Ok(())
+    }
+    fn write_mixed(&mut self, nodes: &[Node]) -> Result<(), XmlError> {
+        use quick_xml::events::{BytesText, Event};
+
+        // indentation would change the content, so the nodes are written
+        // without it and then inserted as a single pre-escaped text event
+        let mut writer = XmlWriter {
+            inner: quick_xml::Writer::new(Vec::new()),
+            namespaces: std::mem::take(&mut self.namespaces),
+            default_prefix: self.default_prefix.clone(),
+            default_namespace_in_scope: self.default_namespace_in_scope,
+            config: self.config.clone(),
+        };
+        for node in nodes {
+            match node {
+                Node::Text(text) => writer.write_text(text)?,
+                Node::Element(name, value) => writer.write_value(name, &[], value)?,
+            }
+        }
+        self.namespaces = writer.namespaces;
+
+        let content = String::from_utf8(writer.inner.into_inner())
+            .expect("the writer only writes valid UTF-8");
+        self.inner
+            .write_event(Event::Text(BytesText::from_escaped(content)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use crate::{properties::DisplayName, FromXml, IntoXml};
+
+    use super::*;
+
+    #[test]
+    fn cdata() -> eyre::Result<()> {
+        let displayname = DisplayName("<b>foo</b> & ]]> bar".into());
+        let config = WriterConfig::new().cdata_threshold(3);
+
+        let xml = displayname.clone().into_xml_with_config(&config)?;
+        assert_str_eq!(
+            std::str::from_utf8(&xml)?,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:displayname xmlns:d="DAV:"><![CDATA[<b>foo</b> & ]]]]><![CDATA[> bar]]></d:displayname>"#
+        );
+        assert_eq!(DisplayName::from_xml(xml)?, displayname);
+
+        let xml = DisplayName("a & b".into()).into_xml_with_config(&config)?;
+        assert!(std::str::from_utf8(&xml)?.contains("a &amp; b"));
+
+        Ok(())
+    }
+
+    const MULTISTATUS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>http://www.example.com/container/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:displayname>Example collection</d:displayname>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response xmlns:x="http://example.com/ns">
+    <d:href>http://www.example.com/container/front.html</d:href>
+    <d:propstat>
+      <d:prop>
+        <x:author>Jane Doe</x:author>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:responsedescription>Done</d:responsedescription>
+</d:multistatus>"#;
+
+    fn responses() -> eyre::Result<Vec<Response>> {
+        use http::StatusCode;
+
+        use crate::elements::{Properties, Propstat, Status};
+
+        struct Author;
+
+        impl Element for Author {
+            const NAMESPACE: &'static str = "http://example.com/ns";
+            const PREFIX: &'static str = "x";
+            const LOCAL_NAME: &'static str = "author";
+        }
+
+        let propstat = |prop| Propstat {
+            prop,
+            status: Status(StatusCode::OK),
+            error: None,
+            responsedescription: None,
+        };
+
+        Ok(vec![
+            Response::Propstat {
+                href: "http://www.example.com/container/".parse()?,
+                propstat: nonempty::nonempty![propstat(
+                    Properties::new().with(DisplayName("Example collection".into()))
+                )],
+                error: None,
+                responsedescription: None,
+                location: None,
+            },
+            Response::Propstat {
+                href: "http://www.example.com/container/front.html".parse()?,
+                propstat: nonempty::nonempty![propstat(
+                    Properties::new()
+                        .with_raw(Author::element_name(), Value::Text("Jane Doe".into()))
+                )],
+                error: None,
+                responsedescription: None,
+                location: None,
+            },
+        ])
+    }
+
+    #[test]
+    fn toplevel() -> eyre::Result<()> {
+        use crate::elements::Propname;
+
+        let xml = Propname.into_xml()?;
+        assert_str_eq!(
+            std::str::from_utf8(&xml)?,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:propname xmlns:d=\"DAV:\"/>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn attributes() -> eyre::Result<()> {
+        use crate::elements::Properties;
+
+        let name = |local_name: &'static str| ElementName {
+            namespace: None,
+            prefix: None,
+            local_name: ByteString::from_static(local_name),
+        };
+        let list = Value::WithAttributes {
+            attributes: [(name("x"), "1".into())].into_iter().collect(),
+            value: Box::new(Value::List(Box::new(nonempty::nonempty![
+                Value::Text("a".into()),
+                Value::Text("b".into()).with_attribute(name("y"), "2"),
+            ]))),
+        };
+        let nested = Value::WithAttributes {
+            attributes: [(name("x"), "1".into()), (name("y"), "1".into())]
+                .into_iter()
+                .collect(),
+            value: Box::new(Value::Empty.with_attribute(name("y"), "2")),
+        };
+        let prop = Properties::new()
+            .with_raw(name("list"), list)
+            .with_raw(name("nested"), nested);
+
+        let config = WriterConfig::new().compact().declaration(false);
+        assert_str_eq!(
+            std::str::from_utf8(&prop.into_xml_with_config(&config)?)?,
+            concat!(
+                r#"<d:prop xmlns:d="DAV:">"#,
+                r#"<list x="1">a</list><list x="1" y="2">b</list>"#,
+                r#"<nested x="1" y="2"/>"#,
+                r#"</d:prop>"#,
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn formatting() -> eyre::Result<()> {
+        use crate::elements::Properties;
+
+        let prop = Properties::new().with(DisplayName("foo".into()));
+
+        let config = WriterConfig::new().compact().declaration(false);
+        assert_str_eq!(
+            std::str::from_utf8(&prop.clone().into_xml_with_config(&config)?)?,
+            r#"<d:prop xmlns:d="DAV:"><d:displayname>foo</d:displayname></d:prop>"#
+        );
+
+        let config = WriterConfig::new().indent(4);
+        assert_str_eq!(
+            std::str::from_utf8(&prop.into_xml_with_config(&config)?)?,
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:prop xmlns:d="DAV:">
+    <d:displayname>foo</d:displayname>
+</d:prop>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_namespace() -> eyre::Result<()> {
+        use crate::elements::Properties;
+
+        let foo = ElementName {
+            namespace: None,
+            prefix: None,
+            local_name: "foo".into(),
+        };
+        let value = Value::Map({
+            let mut map = crate::ValueMap::new();
+            map.insert::<DisplayName>(Value::Text("bar".into()));
+            map
+        });
+        let prop = Properties::new()
+            .with(DisplayName("foo".into()))
+            .with_raw(foo, value);
+
+        let config = WriterConfig::new()
+            .compact()
+            .declaration(false)
+            .default_namespace("DAV:");
+        let xml = prop.clone().into_xml_with_config(&config)?;
+        assert_str_eq!(
+            std::str::from_utf8(&xml)?,
+            concat!(
+                r#"<prop xmlns="DAV:"><displayname>foo</displayname>"#,
+                r#"<foo xmlns=""><displayname xmlns="DAV:">bar</displayname></foo></prop>"#
+            )
+        );
+        assert_eq!(Properties::from_xml(xml)?, prop);
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_namespace_attribute() -> eyre::Result<()> {
+        use crate::elements::Properties;
+
+        let attribute = ElementName {
+            namespace: Some("DAV:".into()),
+            prefix: Some("d".into()),
+            local_name: "foo".into(),
+        };
+        let value = Value::Text("bar".into()).with_attribute(attribute, "baz");
+        let prop = Properties::new().with_raw(DisplayName::element_name(), value);
+
+        let config = WriterConfig::new()
+            .compact()
+            .declaration(false)
+            .default_namespace("DAV:");
+        let xml = prop.clone().into_xml_with_config(&config)?;
+        assert_str_eq!(
+            std::str::from_utf8(&xml)?,
+            r#"<prop xmlns="DAV:" xmlns:d="DAV:"><displayname d:foo="baz">bar</displayname></prop>"#
+        );
+        assert_eq!(Properties::from_xml(xml)?, prop);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes() -> eyre::Result<()> {
+        use crate::elements::Properties;
+
+        fn name(namespace: &'static str, prefix: Option<&'static str>) -> ElementName<ByteString> {
+            ElementName {
+                namespace: Some(namespace.into()),
+                prefix: prefix.map(Into::into),
+                local_name: "foo".into(),
+            }
+        }
+
+        let prop = Properties::new()
+            .with_raw(name("urn:a", None), Value::Empty)
+            .with_raw(name("urn:ietf:params:xml:ns:caldav", None), Value::Empty)
+            // `d` is already used for `DAV:`
+            .with_raw(name("urn:b", Some("d")), Value::Empty)
+            // `C` is reserved for CalDAV
+            .with_raw(name("urn:c", Some("C")), Value::Empty);
+        let config = WriterConfig::new().prefix("urn:ietf:params:xml:ns:caldav", "C");
+
+        let expected = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:prop xmlns:d="DAV:" xmlns:NS0="urn:a" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:NS1="urn:b" xmlns:NS2="urn:c">
+  <NS0:foo/>
+  <C:foo/>
+  <NS1:foo/>
+  <NS2:foo/>
+</d:prop>"#;
+        assert_str_eq!(
+            std::str::from_utf8(&prop.into_xml_with_config(&config)?)?,
+            expected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn multistatus_writer() -> eyre::Result<()> {
+        let mut writer = MultistatusWriter::new(Vec::new());
+        for response in responses()? {
+            writer.write_response(response)?;
+        }
+        writer.write_responsedescription("Done".into())?;
+        let xml = writer.finish()?;
+
+        assert_str_eq!(std::str::from_utf8(&xml)?, MULTISTATUS);
+
+        Ok(())
+    }
+
+    #[test]
+    fn multistatus_writer_empty() -> eyre::Result<()> {
+        let xml = MultistatusWriter::new(Vec::new()).finish()?;
+
+        assert_eq!(
+            crate::elements::Multistatus::from_xml(xml)?,
+            crate::elements::Multistatus::default()
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn multistatus_writer_async() -> eyre::Result<()> {
+        let mut writer = MultistatusWriter::new(Vec::new());
+        for response in responses()? {
+            writer.write_response_async(response).await?;
+        }
+        writer
+            .write_responsedescription_async("Done".into())
+            .await?;
+        let xml = writer.finish_async().await?;
+
+        assert_str_eq!(std::str::from_utf8(&xml)?, MULTISTATUS);
+
         Ok(())
     }
 }
@@ -0,0 +1,285 @@
+// SPDX-FileCopyrightText: d-k-bo <d-k-bo@mailbox.org>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use pretty_assertions::{assert_eq, assert_str_eq};
+use webdav_xml::{
+    elements::{Href, LockEntry, LockScope, LockType, Properties},
+    nonempty::{nonempty, NonEmpty},
+    properties::DisplayName,
+    Element, Error, ExtractElementErrorKind, FromXml, IntoXml,
+};
+
+const OC: &str = "http://owncloud.org/ns";
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(
+    namespace = "http://owncloud.org/ns",
+    prefix = "oc",
+    rename = "share-types"
+)]
+struct ShareTypes {
+    #[dav(text, rename = "share-type")]
+    share_types: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(namespace = "http://owncloud.org/ns", prefix = "oc")]
+struct FileId(#[dav(text)] u64);
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(
+    namespace = "http://owncloud.org/ns",
+    prefix = "oc",
+    rename = "checksums"
+)]
+struct Checksums {
+    #[dav(text)]
+    checksum: NonEmpty<String>,
+}
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(namespace = "http://example.com/ns", prefix = "x")]
+struct Report {
+    href: NonEmpty<Href>,
+    displayname: Option<DisplayName>,
+    lockentry: Vec<LockEntry>,
+    #[dav(text)]
+    size: u64,
+    #[dav(text, namespace = "http://owncloud.org/ns", prefix = "oc")]
+    owner: Option<String>,
+    kind: Kind,
+}
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(namespace = "http://example.com/ns", prefix = "x")]
+enum Kind {
+    File,
+    #[dav(rename = "dir")]
+    Directory,
+    Link(Href),
+    #[dav(rename = "other")]
+    Other(#[dav(text)] String),
+}
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(rename = "lockentry")]
+struct DerivedLockEntry {
+    lockscope: LockScope,
+    locktype: LockType,
+}
+
+#[derive(Debug, PartialEq, Element)]
+#[dav(namespace = "http://example.com/ns", prefix = "x")]
+struct Favorite;
+
+fn trim_xml(xml: &[u8]) -> &str {
+    std::str::from_utf8(xml)
+        .unwrap()
+        .trim_start_matches(r#"<?xml version="1.0" encoding="utf-8"?>"#)
+        .trim()
+}
+
+#[test]
+fn text() -> eyre::Result<()> {
+    let xml = r#"<oc:share-types xmlns:oc="http://owncloud.org/ns">
+  <oc:share-type>0</oc:share-type>
+  <oc:share-type>3</oc:share-type>
+</oc:share-types>"#;
+    let share_types = ShareTypes {
+        share_types: vec![0, 3],
+    };
+    assert_eq!(ShareTypes::from_xml(xml)?, share_types);
+    assert_str_eq!(trim_xml(&share_types.into_xml()?), xml);
+
+    let xml = r#"<oc:share-types xmlns:oc="http://owncloud.org/ns"/>"#;
+    let share_types = ShareTypes {
+        share_types: vec![],
+    };
+    assert_eq!(ShareTypes::from_xml(xml)?, share_types);
+    assert_str_eq!(trim_xml(&share_types.into_xml()?), xml);
+
+    let xml = r#"<oc:fileid xmlns:oc="http://owncloud.org/ns">42</oc:fileid>"#;
+    assert_eq!(FileId::from_xml(xml)?, FileId(42));
+    assert_str_eq!(trim_xml(&FileId(42).into_xml()?), xml);
+
+    assert!(matches!(
+        FileId::from_xml(r#"<oc:fileid xmlns:oc="http://owncloud.org/ns">abc</oc:fileid>"#),
+        Err(Error::ExtractElement(e)) if matches!(e.kind, ExtractElementErrorKind::Other(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn property() -> eyre::Result<()> {
+    // whitespace in properties that aren't defined in RFC 4918 is kept
+    let xml = r#"<d:prop xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <oc:share-types>
+    <oc:share-type>0</oc:share-type>
+    <oc:share-type>3</oc:share-type>
+  </oc:share-types>
+</d:prop>"#;
+    let prop = Properties::from_xml(xml)?;
+    let share_types = prop.get::<ShareTypes>().unwrap().unwrap()?;
+    assert_eq!(
+        share_types,
+        ShareTypes {
+            share_types: vec![0, 3],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn nonempty() -> eyre::Result<()> {
+    let xml = r#"<oc:checksums xmlns:oc="http://owncloud.org/ns">
+  <oc:checksum>SHA1:abc</oc:checksum>
+</oc:checksums>"#;
+    let checksums = Checksums {
+        checksum: nonempty!["SHA1:abc".to_owned()],
+    };
+    assert_eq!(Checksums::from_xml(xml)?, checksums);
+    assert_str_eq!(trim_xml(&checksums.into_xml()?), xml);
+
+    let Err(Error::ExtractElement(e)) =
+        Checksums::from_xml(r#"<oc:checksums xmlns:oc="http://owncloud.org/ns"/>"#)
+    else {
+        panic!("expected an error")
+    };
+    assert!(matches!(
+        e.kind,
+        ExtractElementErrorKind::MissingElement("checksum")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn children() -> eyre::Result<()> {
+    let xml = r#"<x:report xmlns:x="http://example.com/ns" xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <d:href>/a</d:href>
+  <d:href>/b</d:href>
+  <d:displayname>Example</d:displayname>
+  <d:lockentry>
+    <d:lockscope>
+      <d:shared/>
+    </d:lockscope>
+    <d:locktype>
+      <d:write/>
+    </d:locktype>
+  </d:lockentry>
+  <x:size>1234</x:size>
+  <oc:owner>admin</oc:owner>
+  <x:kind>
+    <x:dir/>
+  </x:kind>
+</x:report>"#;
+    let report = Report {
+        href: nonempty![Href("/a".parse()?), Href("/b".parse()?)],
+        displayname: Some(DisplayName("Example".into())),
+        lockentry: vec![LockEntry {
+            lockscope: LockScope::Shared,
+            locktype: LockType::Write,
+        }],
+        size: 1234,
+        owner: Some("admin".into()),
+        kind: Kind::Directory,
+    };
+    assert_eq!(Report::from_xml(xml)?, report);
+    assert_str_eq!(trim_xml(&report.into_xml()?), xml);
+
+    let xml = r#"<x:report xmlns:x="http://example.com/ns" xmlns:d="DAV:">
+  <d:href>/a</d:href>
+  <x:kind>
+    <x:file/>
+  </x:kind>
+</x:report>"#;
+    let Err(Error::ExtractElement(e)) = Report::from_xml(xml) else {
+        panic!("expected an error")
+    };
+    assert!(matches!(
+        e.kind,
+        ExtractElementErrorKind::MissingElement("size")
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn choice() -> eyre::Result<()> {
+    let cases = [
+        (r#"<x:file/>"#, Kind::File),
+        (r#"<x:dir/>"#, Kind::Directory),
+        (r#"<d:href>/a</d:href>"#, Kind::Link(Href("/a".parse()?))),
+        (r#"<x:other>socket</x:other>"#, Kind::Other("socket".into())),
+    ];
+    for (child, kind) in cases {
+        let xml =
+            format!(r#"<x:kind xmlns:x="http://example.com/ns" xmlns:d="DAV:">{child}</x:kind>"#);
+        assert_eq!(Kind::from_xml(xml)?, kind);
+    }
+
+    let xml = r#"<x:kind xmlns:x="http://example.com/ns">
+  <x:other>socket</x:other>
+</x:kind>"#;
+    assert_str_eq!(trim_xml(&Kind::Other("socket".into()).into_xml()?), xml);
+
+    let xml = r#"<x:kind xmlns:x="http://example.com/ns"/>"#;
+    let Err(Error::ExtractElement(e)) = Kind::from_xml(xml) else {
+        panic!("expected an error")
+    };
+    assert!(matches!(
+        e.kind,
+        ExtractElementErrorKind::MissingElement("file")
+    ));
+
+    let xml = r#"<x:kind xmlns:x="http://example.com/ns"><x:file/><x:dir/></x:kind>"#;
+    let Err(Error::ExtractElement(e)) = Kind::from_xml(xml) else {
+        panic!("expected an error")
+    };
+    assert!(matches!(
+        e.kind,
+        ExtractElementErrorKind::ConflictingElements(&["file", "dir", "href", "other"])
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn matches_handwritten() -> eyre::Result<()> {
+    let lockentry = LockEntry {
+        lockscope: LockScope::Exclusive,
+        locktype: LockType::Write,
+    };
+    let derived = DerivedLockEntry {
+        lockscope: LockScope::Exclusive,
+        locktype: LockType::Write,
+    };
+
+    let xml = lockentry.into_xml()?;
+    assert_eq!(derived.into_xml()?, xml);
+    assert_eq!(
+        DerivedLockEntry::from_xml(xml)?,
+        DerivedLockEntry {
+            lockscope: LockScope::Exclusive,
+            locktype: LockType::Write,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn empty() -> eyre::Result<()> {
+    let xml = r#"<x:favorite xmlns:x="http://example.com/ns"/>"#;
+    assert_eq!(Favorite::from_xml(xml)?, Favorite);
+    assert_str_eq!(trim_xml(&Favorite.into_xml()?), xml);
+
+    assert_eq!(Favorite::NAMESPACE, "http://example.com/ns");
+    assert_eq!(FileId::LOCAL_NAME, "fileid");
+    assert_eq!(<FileId as Element>::NAMESPACE, OC);
+
+    Ok(())
+}